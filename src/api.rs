@@ -0,0 +1,212 @@
+use std::fmt;
+
+use error_stack::{Context, Report, Result};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::StatusCode;
+use serenity::model::id::UserId;
+use tracing::debug;
+
+use crate::models::{Addon, ErrorResponse, User};
+
+#[derive(Debug)]
+pub struct ApiError;
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Error whilst talking to the Pulsar Link backend")
+    }
+}
+
+impl Context for ApiError {}
+
+/// An identity a Pulsar Link user can be found by.
+pub enum UserLookup<'a> {
+    PulsarId(&'a str),
+    Discord(UserId),
+    Steam(&'a str),
+    Gmodstore(&'a str),
+}
+
+impl UserLookup<'_> {
+    /// The backend path for the lookup. IDs are percent-encoded, as some come straight from user
+    /// input and could otherwise reach another endpoint.
+    fn path(&self) -> String {
+        let encode = |id: &str| utf8_percent_encode(id, NON_ALPHANUMERIC).to_string();
+
+        match self {
+            UserLookup::PulsarId(id) => format!("user/{}", encode(id)),
+            UserLookup::Discord(id) => format!("user/{}/discord", id),
+            UserLookup::Steam(id) => format!("user/{}/steam", encode(id)),
+            UserLookup::Gmodstore(id) => format!("user/{}/gmodstore", encode(id)),
+        }
+    }
+}
+
+/// Thin client for the Pulsar Link backend, cheap to clone into background tasks.
+#[derive(Clone)]
+pub struct PulsarApi {
+    http: reqwest::Client,
+    url: String,
+    key: String,
+}
+
+impl PulsarApi {
+    pub fn new(http: reqwest::Client, url: &str, key: &str) -> Self {
+        Self {
+            http,
+            url: url.trim_end_matches('/').to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    /// Looks up the Pulsar Link user tied to a Discord account, `None` if they aren't linked.
+    pub async fn user_by_discord(&self, user_id: UserId) -> Result<Option<User>, ApiError> {
+        self.find_user(UserLookup::Discord(user_id)).await
+    }
+
+    /// Looks up a Pulsar Link user, `None` if no user has that identity.
+    pub async fn find_user(&self, lookup: UserLookup<'_>) -> Result<Option<User>, ApiError> {
+        let response = self.get(&lookup.path()).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let user: User = Self::parse(response).await?;
+
+        if user.id.is_none() {
+            debug!(
+                "Backend returned no user for /{}: {}",
+                lookup.path(),
+                user.error.as_deref().unwrap_or("no error given")
+            );
+
+            return Ok(None);
+        }
+
+        Ok(Some(user))
+    }
+
+    pub async fn user_addons(&self, pulsar_id: &str) -> Result<Vec<Addon>, ApiError> {
+        let response = self.get(&format!("user/{}/addons", pulsar_id)).await?;
+
+        Self::parse(response).await
+    }
+
+    /// Everything the backend holds about a user, as returned by the backend.
+    pub async fn user_export(&self, pulsar_id: &str) -> Result<serde_json::Value, ApiError> {
+        let user: serde_json::Value =
+            Self::parse(self.get(&format!("user/{}", pulsar_id)).await?).await?;
+        let addons: serde_json::Value =
+            Self::parse(self.get(&format!("user/{}/addons", pulsar_id)).await?).await?;
+
+        Ok(serde_json::json!({ "user": user, "addons": addons }))
+    }
+
+    /// Pulsar Link users found for the Steam or Gmodstore identity of `user`, including `user`.
+    /// The backend returns one user per identity and refuses to link an identity twice, so this
+    /// only finds others for links made before that was enforced.
+    pub async fn linked_users(&self, user: &User) -> Result<Vec<User>, ApiError> {
+        let mut users = vec![user.clone()];
+        let steam_id = user.steam_id.map(|id| id.to_string());
+
+        let lookups = [
+            steam_id.as_deref().map(UserLookup::Steam),
+            user.gmodstore_id.as_deref().map(UserLookup::Gmodstore),
+        ];
+
+        for lookup in lookups.into_iter().flatten() {
+            if let Some(linked) = self.find_user(lookup).await? {
+                if !users.iter().any(|known| known.id == linked.id) {
+                    users.push(linked);
+                }
+            }
+        }
+
+        Ok(users)
+    }
+
+    /// Marks the user as banned, so the backend can refuse to link their identities again.
+    pub async fn flag_banned(&self, pulsar_id: &str, reason: Option<&str>) -> Result<(), ApiError> {
+        let path = format!("user/{}/ban", pulsar_id);
+        let response = self
+            .http
+            .post(format!("{}/{}", self.url, path))
+            .header("Authorization", self.key.as_str())
+            .json(&serde_json::json!({ "reason": reason }))
+            .send()
+            .await
+            .map_err(|e| {
+                Report::from(e)
+                    .change_context(ApiError)
+                    .attach_printable(format!("Failed to send request to /{}", path))
+            })?;
+
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(Report::from(ApiError).attach_printable(format!(
+                "Backend responded with {} to POST /{}",
+                status, path
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> Result<reqwest::Response, ApiError> {
+        self.http
+            .get(format!("{}/{}", self.url, path))
+            .header("Authorization", self.key.as_str())
+            .send()
+            .await
+            .map_err(|e| {
+                Report::from(e)
+                    .change_context(ApiError)
+                    .attach_printable(format!("Failed to send request to /{}", path))
+            })
+    }
+
+    async fn parse<T: serde::de::DeserializeOwned>(
+        response: reqwest::Response,
+    ) -> Result<T, ApiError> {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| Report::from(e).change_context(ApiError))?;
+
+        if !status.is_success() {
+            let error = serde_json::from_str::<ErrorResponse>(&body)
+                .ok()
+                .and_then(|e| e.error)
+                .unwrap_or(body);
+
+            return Err(Report::from(ApiError)
+                .attach_printable(format!("Backend responded with {}: {}", status, error)));
+        }
+
+        serde_json::from_str(&body).map_err(|e| {
+            Report::from(e)
+                .change_context(ApiError)
+                .attach_printable("Failed to decode backend response")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_lookup_ids() {
+        assert_eq!(
+            UserLookup::Gmodstore("../user/1?x=y").path(),
+            "user/%2E%2E%2Fuser%2F1%3Fx%3Dy/gmodstore"
+        );
+        assert_eq!(
+            UserLookup::Steam("76561198000000000").path(),
+            "user/76561198000000000/steam"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use error_stack::{Context, Report, Result, ResultExt};
+use error_stack::{Context, Result, ResultExt};
 use serenity::{all::GatewayIntents, Client};
 use tracing::{info, trace};
 
@@ -20,8 +20,7 @@ impl Context for BotStartError {}
 pub async fn start() -> Result<(), BotStartError> {
     trace!("Reading config file :3");
     let config = ConfigFile::read().map_err(|e| {
-        Report::from(e)
-            .attach_printable("Failed to read config file.")
+        e.attach_printable("Failed to read config file.")
             .change_context(BotStartError)
     })?;
 
@@ -1,11 +1,14 @@
 use async_trait::async_trait;
-use error_stack::{IntoReport, Report, Result, ResultExt};
-use serde::Deserialize;
-use serenity::all::{CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage};
+use error_stack::Result;
+use serenity::all::{CommandDataOptionValue, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption, CreateInteractionResponseMessage};
+use tracing::error;
 
-use crate::event_handler::BotEvents;
+use crate::{api::UserLookup, event_handler::BotEvents};
 
-use super::{Command, CommandExecutionError, CommandInfo};
+use super::{
+    response::{public_option, respond_lookup},
+    Command, CommandExecutionError, CommandInfo,
+};
 
 #[derive(Debug)]
 pub struct UserAddonsCommand;
@@ -20,25 +23,55 @@ impl CommandInfo for UserAddonsCommand {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct Addon {
-    id: Option<String>,
-    name: Option<String>,
-    error: Option<String>,
-}
+/// Lists a Pulsar Link user's owned addons, shared with the context menus.
+pub async fn describe_addons(
+    handler: &BotEvents,
+    lookup: UserLookup<'_>,
+) -> CreateInteractionResponseMessage {
+    let pulsar_id = match lookup {
+        UserLookup::PulsarId(id) => Ok(Some(id.to_string())),
+        lookup => handler
+            .api
+            .find_user(lookup)
+            .await
+            .map(|user| user.and_then(|user| user.id)),
+    };
+
+    let pulsar_id = match pulsar_id {
+        Ok(Some(pulsar_id)) => pulsar_id,
+        Ok(None) => {
+            return CreateInteractionResponseMessage::new()
+                .content("No Pulsar Link user was found.");
+        }
+        Err(err) => {
+            error!("Failed to look up user: {:?}", err);
 
-#[derive(Deserialize)]
-struct ErrorResponse {
-    error: String,
-}
+            return CreateInteractionResponseMessage::new()
+                .content("An error occurred while trying to get the user. Please try again later.");
+        }
+    };
+
+    let addons_list = match handler.api.user_addons(&pulsar_id).await {
+        Ok(addons) => addons,
+        Err(err) => {
+            error!("Failed to get addons for {}: {:?}", pulsar_id, err);
+
+            return CreateInteractionResponseMessage::new().content(
+                "An error occurred while trying to get the user's addons. Please try again later.",
+            );
+        }
+    };
 
-#[derive(Debug, Deserialize)]
-struct User {
-    id: Option<String>,
-    steam_id: Option<u64>,
-    gmodstore_id: Option<String>,
-    discord_id: Option<u64>,
-    error: Option<String>,
+    let mut addons_string = String::new();
+    addons_string.push_str("User's owned addons:\n");
+
+    for addon in addons_list.iter().filter(|addon| addon.error.is_none()) {
+        let id = addon.id.as_deref().unwrap_or("");
+        let name = addon.name.as_deref().unwrap_or("");
+        addons_string.push_str(&format!("[{}](<https://www.gmodstore.com/market/view/{}>)\n", name, id));
+    }
+
+    CreateInteractionResponseMessage::new().content(addons_string)
 }
 
 #[async_trait]
@@ -49,119 +82,24 @@ impl Command for UserAddonsCommand {
         ctx: &Context,
         interaction: &'a mut CommandInteraction,
     ) -> Result<(), CommandExecutionError> {
-        let option = interaction.data.options.get(0);
-
-        let mut pulsar_id: String = "unknown".to_string();
-
-        match option.unwrap().name.as_str() {
-            "id" => {
-                pulsar_id = option.unwrap().value.as_str().unwrap().to_string();
-            }
-            "discord_user" => {
-                let target_user = option.unwrap().value.as_user_id().unwrap();
-
-                let url = format!("{}/user/{}/discord", handler.cfg.api_url, target_user);
-
-                let client = reqwest::Client::new();
-                let response = client
-                    .get(url.to_owned())
-                    .header("Authorization", handler.cfg.api_key.as_str())
-                    .send()
-                    .await
-                    .expect("Failed to send request");
-
-                let user: User = response
-                    .json()
-                    .await
-                    .expect("Failed to deserialize response body");
-
-                if user.error != None {
-                    let message = CreateInteractionResponseMessage::new().content(format!(
-                        "An error occurred while trying to get the user: {}",
-                        user.error.unwrap()
-                    ));
-
-                    let builder = CreateInteractionResponse::Message(message);
-
-                    interaction
-                        .create_response(&ctx.http, builder)
-                        .await
-                        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-
-                    return Ok(());
+        let lookup = interaction
+            .data
+            .options
+            .iter()
+            .find_map(|option| match (option.name.as_str(), &option.value) {
+                ("id", CommandDataOptionValue::String(id)) => Some(UserLookup::PulsarId(id)),
+                ("discord_user", CommandDataOptionValue::User(user_id)) => {
+                    Some(UserLookup::Discord(*user_id))
                 }
+                _ => None,
+            });
 
-                pulsar_id = user.id.unwrap();
-            }
-            _ => {
-                let message = CreateInteractionResponseMessage::new().content("No data provided.".to_string());
-
-                let builder = CreateInteractionResponse::Message(message);
-
-                interaction
-                    .create_response(&ctx.http, builder)
-                    .await
-                    .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-            }
+        let message = match lookup {
+            Some(lookup) => describe_addons(handler, lookup).await,
+            None => CreateInteractionResponseMessage::new().content("No data provided.".to_string()),
         };
 
-        let client = reqwest::Client::new();
-        let response = client
-            .get(format!("{}/user/{}/addons", handler.cfg.api_url, pulsar_id))
-            .header("Authorization", handler.cfg.api_key.as_str())
-            .send()
-            .await
-            .expect("Failed to send request");
-
-        let response_text = response.text().await.expect("Failed to get response text");
-
-        let addons_result: Result<Vec<Addon>, _> =
-            serde_json::from_str(&response_text).map_err(|err| error_stack::Report::from(err));
-
-        let addons_list;
-        match addons_result {
-            Ok(addons) => {
-                addons_list = addons;
-            }
-            Err(_) => {
-                let error_response: ErrorResponse = serde_json::from_str(&response_text)
-                    .expect("Failed to deserialize error response");
-
-                let message = CreateInteractionResponseMessage::new().content(format!(
-                    "An error occurred while trying to get the user's addons: {}",
-                    error_response.error.to_string()
-                ));
-
-                let builder = CreateInteractionResponse::Message(message);
-
-                interaction
-                    .create_response(&ctx.http, builder)
-                    .await
-                    .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-
-                return Ok(());
-            }
-        }
-
-        let mut addons_string = String::new();
-        addons_string.push_str("User's owned addons:\n");
-
-        for addon in &addons_list {
-            let id = addon.id.as_deref().unwrap_or("");
-            let name = addon.name.as_deref().unwrap_or("");
-            addons_string.push_str(&format!("[{}](<https://www.gmodstore.com/market/view/{}>)\n", name, id));
-        }
-
-        let message = CreateInteractionResponseMessage::new().content(addons_string);
-
-        let builder = CreateInteractionResponse::Message(message);
-
-        interaction
-            .create_response(&ctx.http, builder)
-            .await
-            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-
-        return Ok(());
+        respond_lookup(handler, ctx, interaction, message).await
     }
 
     async fn register(&self, _: &BotEvents) -> CreateCommand {
@@ -178,6 +116,7 @@ impl Command for UserAddonsCommand {
                     "The users Discord account.",
                 ).required(false),
             )
+            .add_option(public_option())
             .dm_permission(false)
     }
 }
@@ -0,0 +1,571 @@
+use error_stack::{Report, Result, ResultExt};
+use serenity::{
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateInputText, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+        CreateModal, EditInteractionResponse,
+    },
+    client::Context,
+    model::{
+        application::{
+            ActionRowComponent, ButtonStyle, ComponentInteraction, InputTextStyle, ModalInteraction,
+        },
+        channel::Message,
+        id::{ChannelId, GuildId, UserId},
+    },
+};
+use tracing::{error, warn};
+
+use crate::{
+    database::{
+        appeals::{Appeal, AppealStatus, NewAppeal},
+        cases::{Case, CaseAction},
+    },
+    event_handler::BotEvents,
+};
+
+use super::{
+    moderation::{describe_case, describe_notice, find_member, guild_name, notify, truncate},
+    unban::lift_ban,
+    CommandExecutionError,
+};
+
+/// Longest appeal or review note accepted, short enough to fit in an embed field.
+const MAX_APPEAL_LENGTH: u16 = 1000;
+
+/// Discord caps embed field values at 1024 characters.
+const FIELD_LIMIT: usize = 1024;
+
+/// The custom ID of the appeal button and form for a ban, carrying the ban's case if it was
+/// recorded.
+fn appeal_id(guild_id: GuildId, case_id: Option<i64>) -> String {
+    match case_id {
+        Some(case_id) => format!("ban:appeal:{}:{}", guild_id, case_id),
+        None => format!("ban:appeal:{}", guild_id),
+    }
+}
+
+/// The button added to ban notices, which opens the appeal form.
+pub fn appeal_button(guild_id: GuildId, case: Option<&Case>) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(appeal_id(
+        guild_id,
+        case.map(|case| case.id),
+    ))
+    .label("Appeal")
+    .style(ButtonStyle::Secondary)])
+}
+
+/// The staff channel a guild's appeals are sent to, if it takes appeals.
+fn appeal_channel(handler: &BotEvents, guild_id: GuildId) -> Option<ChannelId> {
+    handler
+        .cfg
+        .guild(guild_id.get())
+        .and_then(|settings| settings.ban_notify.appeal_channel)
+        .map(ChannelId::new)
+}
+
+/// Whether the user may review a guild's appeals, which takes Ban Members in that guild. The staff
+/// channel may be in another server, so permissions there don't count.
+async fn can_review(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: UserId,
+) -> Result<bool, CommandExecutionError> {
+    let Some(member) = find_member(ctx, guild_id, user).await? else {
+        return Ok(false);
+    };
+
+    let guild = guild_id
+        .to_partial_guild(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    Ok(guild.member_permissions(&member).ban_members())
+}
+
+/// Why the user may not review the appeal, if they may not.
+async fn review_refusal(
+    ctx: &Context,
+    appeal: Option<&Appeal>,
+    user: UserId,
+) -> Result<Option<&'static str>, CommandExecutionError> {
+    let Some(appeal) = appeal.filter(|appeal| appeal.status == AppealStatus::Pending) else {
+        return Ok(Some("This appeal has already been reviewed"));
+    };
+
+    if !can_review(ctx, GuildId::new(appeal.guild_id), user).await? {
+        return Ok(Some(
+            "You need the Ban Members permission in the appeal's server to review it",
+        ));
+    }
+
+    Ok(None)
+}
+
+fn ephemeral(content: impl Into<String>) -> CreateInteractionResponse {
+    CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .ephemeral(true),
+    )
+}
+
+/// Tells the reviewer something privately after their response was deferred, leaving the appeal's
+/// message as it is.
+async fn followup(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    content: &str,
+) -> Result<(), CommandExecutionError> {
+    interaction
+        .create_followup(
+            &ctx.http,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+}
+
+fn input(interaction: &ModalInteraction, custom_id: &str) -> Option<String> {
+    interaction
+        .data
+        .components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find_map(|component| match component {
+            ActionRowComponent::InputText(input) if input.custom_id == custom_id => {
+                input.value.clone()
+            }
+            _ => None,
+        })
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Why the user can't appeal their ban in the guild right now, if they can't.
+async fn appeal_refusal(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild_id: GuildId,
+    user: UserId,
+) -> Result<Option<&'static str>, CommandExecutionError> {
+    if appeal_channel(handler, guild_id).is_none() {
+        return Ok(Some("This server no longer takes ban appeals"));
+    }
+
+    let ban = ctx
+        .http
+        .get_ban(guild_id, user)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    if ban.is_none() {
+        return Ok(Some("You're no longer banned from this server"));
+    }
+
+    let pending = handler
+        .db
+        .pending_appeal(guild_id.get(), user.get())
+        .change_context(CommandExecutionError)?;
+
+    Ok(pending.map(|_| "Your last appeal is still waiting for a review"))
+}
+
+/// The staff message announcing an appeal, with the buttons to review it.
+fn appeal_message(appeal: &Appeal, reason: Option<&str>) -> CreateMessage {
+    let embed = CreateEmbed::new()
+        .title(format!("Ban appeal #{}", appeal.id))
+        .field(
+            "User",
+            format!("<@{}> (`{}`)", appeal.user_id, appeal.user_id),
+            true,
+        )
+        .field(
+            "Case",
+            appeal
+                .case_id
+                .map(|id| format!("#{}", id))
+                .unwrap_or_else(|| String::from("None recorded")),
+            true,
+        )
+        .field(
+            "Ban reason",
+            truncate(reason.unwrap_or("No reason given"), FIELD_LIMIT),
+            false,
+        )
+        .field("Appeal", truncate(&appeal.message, FIELD_LIMIT), false);
+
+    let buttons = vec![
+        CreateButton::new(format!("ban:appeal-accept:{}", appeal.id))
+            .label("Accept")
+            .style(ButtonStyle::Success),
+        CreateButton::new(format!("ban:appeal-deny:{}", appeal.id))
+            .label("Deny")
+            .style(ButtonStyle::Danger),
+    ];
+
+    CreateMessage::new()
+        .embed(embed)
+        .components(vec![CreateActionRow::Buttons(buttons)])
+}
+
+/// The appeal's staff embed with the decision added to it.
+fn reviewed(message: Option<&Message>, decision: String, note: Option<&str>) -> CreateEmbed {
+    let mut embed = message
+        .and_then(|message| message.embeds.first().cloned())
+        .map(CreateEmbed::from)
+        .unwrap_or_default()
+        .field("Decision", decision, false);
+
+    if let Some(note) = note {
+        embed = embed.field("Note", note, false);
+    }
+
+    embed
+}
+
+/// Handles the appeal button in ban notices and the review buttons on staff messages.
+pub async fn component(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+) -> Result<(), CommandExecutionError> {
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let (Some(action), Some(id)) = (
+        parts.next(),
+        parts.next().and_then(|id| id.parse::<u64>().ok()),
+    ) else {
+        return Err(Report::from(CommandExecutionError)
+            .attach_printable(format!("Unknown component {}", interaction.data.custom_id)));
+    };
+    let case_id = parts.next().and_then(|id| id.parse::<i64>().ok());
+
+    let response = match action {
+        "appeal" => {
+            let guild_id = GuildId::new(id);
+
+            match appeal_refusal(handler, ctx, guild_id, interaction.user.id).await? {
+                Some(refusal) => ephemeral(refusal),
+                None => {
+                    let text = CreateInputText::new(
+                        InputTextStyle::Paragraph,
+                        "Why should your ban be lifted?",
+                        "message",
+                    )
+                    .max_length(MAX_APPEAL_LENGTH);
+
+                    CreateInteractionResponse::Modal(
+                        CreateModal::new(appeal_id(guild_id, case_id), "Ban appeal")
+                            .components(vec![CreateActionRow::InputText(text)]),
+                    )
+                }
+            }
+        }
+        "appeal-accept" => return accept(handler, ctx, interaction, id as i64).await,
+        "appeal-deny" => {
+            let appeal = handler
+                .db
+                .appeal(id as i64)
+                .change_context(CommandExecutionError)?;
+
+            if let Some(refusal) = review_refusal(ctx, appeal.as_ref(), interaction.user.id).await?
+            {
+                ephemeral(refusal)
+            } else {
+                let text =
+                    CreateInputText::new(InputTextStyle::Paragraph, "Note for staff", "note")
+                        .max_length(MAX_APPEAL_LENGTH)
+                        .required(false);
+
+                CreateInteractionResponse::Modal(
+                    CreateModal::new(
+                        format!("ban:appeal-deny:{}", id),
+                        format!("Deny appeal #{}", id),
+                    )
+                    .components(vec![CreateActionRow::InputText(text)]),
+                )
+            }
+        }
+        _ => {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable(format!("Unknown component {}", interaction.data.custom_id)))
+        }
+    };
+
+    interaction
+        .create_response(&ctx.http, response)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+}
+
+/// Handles a submitted appeal or denial.
+pub async fn modal(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &ModalInteraction,
+) -> Result<(), CommandExecutionError> {
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let (Some(action), Some(id)) = (
+        parts.next(),
+        parts.next().and_then(|id| id.parse::<u64>().ok()),
+    ) else {
+        return Err(Report::from(CommandExecutionError)
+            .attach_printable(format!("Unknown modal {}", interaction.data.custom_id)));
+    };
+    let case_id = parts.next().and_then(|id| id.parse::<i64>().ok());
+
+    let response = match action {
+        "appeal" => submit(handler, ctx, interaction, GuildId::new(id), case_id).await?,
+        "appeal-deny" => deny(handler, ctx, interaction, id as i64).await?,
+        _ => {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable(format!("Unknown modal {}", interaction.data.custom_id)))
+        }
+    };
+
+    interaction
+        .create_response(&ctx.http, response)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+}
+
+/// Records an appeal against the ban case from the user's ban notice and sends it to the guild's
+/// staff.
+async fn submit(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &ModalInteraction,
+    guild_id: GuildId,
+    case_id: Option<i64>,
+) -> Result<CreateInteractionResponse, CommandExecutionError> {
+    let user = interaction.user.id;
+
+    // Checked again, the form may have been open a while
+    if let Some(refusal) = appeal_refusal(handler, ctx, guild_id, user).await? {
+        return Ok(ephemeral(refusal));
+    }
+
+    let Some(channel) = appeal_channel(handler, guild_id) else {
+        return Ok(ephemeral("This server no longer takes ban appeals"));
+    };
+
+    let Some(message) = input(interaction, "message") else {
+        return Ok(ephemeral("Your appeal can't be empty"));
+    };
+
+    let case = match case_id {
+        Some(case_id) => handler
+            .db
+            .case(guild_id.get(), case_id)
+            .change_context(CommandExecutionError)?
+            .filter(|case| case.action == CaseAction::Ban && case.target_id == user.get()),
+        None => None,
+    };
+
+    let appeal = handler
+        .db
+        .create_appeal(NewAppeal {
+            guild_id: guild_id.get(),
+            user_id: user.get(),
+            case_id: case.as_ref().map(|case| case.id),
+            message,
+        })
+        .change_context(CommandExecutionError)?;
+
+    // The staff message needs the appeal's ID, so the appeal is forgotten again if it can't be
+    // posted, rather than blocking every retry as pending
+    if let Err(err) = channel
+        .send_message(
+            &ctx.http,
+            appeal_message(
+                &appeal,
+                case.as_ref().and_then(|case| case.reason.as_deref()),
+            ),
+        )
+        .await
+    {
+        error!("Failed to post appeal #{}: {:?}", appeal.id, err);
+
+        if let Err(err) = handler.db.delete_appeal(appeal.id) {
+            error!("Failed to forget unsent appeal #{}: {:?}", appeal.id, err);
+        }
+
+        return Ok(ephemeral(
+            "Your appeal couldn't be sent to the staff. Please try again later.",
+        ));
+    }
+
+    Ok(ephemeral(format!(
+        "Your appeal has been sent to the staff of {}. You'll get a DM once it's been reviewed.",
+        guild_name(ctx, guild_id)
+    )))
+}
+
+/// Accepts an appeal, lifting the ban in the appeal's guild and letting the user know. The appeal
+/// stays pending if the ban can't be lifted.
+async fn accept(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    id: i64,
+) -> Result<(), CommandExecutionError> {
+    let appeal = handler
+        .db
+        .appeal(id)
+        .change_context(CommandExecutionError)?;
+
+    if let Some(refusal) = review_refusal(ctx, appeal.as_ref(), interaction.user.id).await? {
+        return interaction
+            .create_response(&ctx.http, ephemeral(refusal))
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError));
+    }
+
+    // Only pending appeals get past the refusal
+    let Some(appeal) = appeal else {
+        return Ok(());
+    };
+
+    interaction
+        .defer(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let guild_id = GuildId::new(appeal.guild_id);
+    let user = UserId::new(appeal.user_id);
+    let reason = format!("Appeal #{} accepted", appeal.id);
+
+    let unbanned = match lift_ban(
+        handler,
+        ctx,
+        guild_id,
+        interaction.user.id,
+        user,
+        Some(&reason),
+    )
+    .await
+    {
+        Ok(unbanned) => unbanned,
+        Err(err) => {
+            warn!("Failed to lift ban for appeal #{}: {:?}", appeal.id, err);
+
+            return followup(
+                ctx,
+                interaction,
+                "Lifting the ban failed, so the appeal is still pending. Try again, or use `/unban` and deny it.",
+            )
+            .await;
+        }
+    };
+
+    // Reviewed by someone else while the ban was being lifted
+    if handler
+        .db
+        .review_appeal(id, AppealStatus::Accepted, interaction.user.id.get(), None)
+        .change_context(CommandExecutionError)?
+        .is_none()
+    {
+        return followup(ctx, interaction, "This appeal has already been reviewed").await;
+    }
+
+    let mut decision = match unbanned {
+        Some(unbanned) => {
+            let mut decision = format!(
+                "Accepted by <@{}>, unbanned{}",
+                interaction.user.id,
+                describe_case(unbanned.case.as_ref())
+            );
+
+            if let Some(problem) = unbanned.schedule_problem {
+                decision.push_str(&format!("\n⚠️ {}", problem));
+            }
+
+            decision
+        }
+        None => format!(
+            "Accepted by <@{}>, they were no longer banned",
+            interaction.user.id
+        ),
+    };
+
+    let notice = CreateEmbed::new()
+        .title(format!(
+            "Your ban appeal in {} was accepted",
+            guild_name(ctx, guild_id)
+        ))
+        .description("You've been unbanned and are welcome to rejoin.");
+
+    decision.push_str(&describe_notice(
+        Some(notify(ctx, user, notice).await),
+        "decision",
+    ));
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .embed(reviewed(Some(&interaction.message), decision, None))
+                .components(vec![]),
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+}
+
+/// Denies an appeal with the reviewer's note, which is kept from the user.
+async fn deny(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &ModalInteraction,
+    id: i64,
+) -> Result<CreateInteractionResponse, CommandExecutionError> {
+    let appeal = handler
+        .db
+        .appeal(id)
+        .change_context(CommandExecutionError)?;
+
+    if let Some(refusal) = review_refusal(ctx, appeal.as_ref(), interaction.user.id).await? {
+        return Ok(ephemeral(refusal));
+    }
+
+    let note = input(interaction, "note");
+
+    let Some(appeal) = handler
+        .db
+        .review_appeal(
+            id,
+            AppealStatus::Denied,
+            interaction.user.id.get(),
+            note.as_deref(),
+        )
+        .change_context(CommandExecutionError)?
+    else {
+        return Ok(ephemeral("This appeal has already been reviewed"));
+    };
+
+    let notice = CreateEmbed::new().title(format!(
+        "Your ban appeal in {} was denied",
+        guild_name(ctx, GuildId::new(appeal.guild_id))
+    ));
+
+    let decision = format!(
+        "Denied by <@{}>{}",
+        interaction.user.id,
+        describe_notice(
+            Some(notify(ctx, UserId::new(appeal.user_id), notice).await),
+            "decision"
+        )
+    );
+
+    Ok(CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .embed(reviewed(
+                interaction.message.as_deref(),
+                decision,
+                note.as_deref(),
+            ))
+            .components(vec![]),
+    ))
+}
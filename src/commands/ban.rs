@@ -1,20 +1,48 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 use error_stack::{Report, Result};
 use serenity::{
-    builder::{
-        CreateCommand, CreateCommandOption, CreateInteractionResponse,
-        CreateInteractionResponseMessage,
-    },
+    builder::{CreateCommand, CreateCommandOption, CreateEmbed, CreateMessage},
     client::Context,
     model::{
-        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        application::{
+            CommandDataOptionValue, CommandInteraction, CommandOptionType, ComponentInteraction,
+            ModalInteraction,
+        },
+        guild::{Member, PartialGuild},
+        id::{GuildId, UserId},
         Permissions,
     },
 };
+use tracing::{error, warn};
+
+use crate::{
+    config::DeleteMessages,
+    database::{
+        cases::{Case, CaseAction, NewCase},
+        tempbans::TempBan,
+    },
+    duration::{self, unix_now},
+    event_handler::BotEvents,
+};
+
+use super::{
+    appeals,
+    moderation::{
+        self, describe_case, describe_notice, download_id_list, edit_reply, find_member,
+        guild_name, id_list_too_large, moderation_context, notice, parse_user_ids, post_case,
+        record_case, refusal, reply_ephemeral, retract_notice, send_notice, should_notify,
+        sync_guild, sync_guilds, synced_reason, syncs_bans,
+    },
+    Command, CommandExecutionError, CommandInfo,
+};
 
-use crate::event_handler::BotEvents;
+/// Most users a single hackban will go through.
+const MAX_HACKBAN_TARGETS: usize = 200;
 
-use super::{Command, CommandExecutionError, CommandInfo};
+/// Most skipped or failed users listed individually in a hackban summary.
+const REPORTED_PROBLEMS: usize = 15;
 
 #[derive(Debug)]
 pub struct BanCommand;
@@ -29,170 +57,935 @@ impl CommandInfo for BanCommand {
     }
 }
 
-#[async_trait]
-impl Command for BanCommand {
-    async fn execute<'a>(
-        &self,
-        _handler: &BotEvents,
-        ctx: &Context,
-        interaction: &'a mut CommandInteraction,
-    ) -> Result<(), CommandExecutionError> {
-        let interaction_member = match &interaction.member {
-            Some(member) => member,
-            None => {
-                return Err(Report::from(CommandExecutionError)
-                    .attach_printable("Failed to fetch member from interaction"))
+/// The message history to delete, falling back to the guild's configured default.
+pub fn delete_window(
+    handler: &BotEvents,
+    guild_id: GuildId,
+    choice: Option<DeleteMessages>,
+) -> DeleteMessages {
+    choice.unwrap_or_else(|| {
+        handler
+            .cfg
+            .guild(guild_id.get())
+            .map(|settings| settings.ban_delete_messages)
+            .unwrap_or_default()
+    })
+}
+
+pub fn describe_deletion(delete_messages: DeleteMessages) -> &'static str {
+    match delete_messages {
+        DeleteMessages::None => "",
+        DeleteMessages::Hour => " and deleted their messages from the last hour",
+        DeleteMessages::Day => " and deleted their messages from the last 24 hours",
+        DeleteMessages::Week => " and deleted their messages from the last 7 days",
+    }
+}
+
+/// Everything about a ban besides who is banned.
+#[derive(Debug, Default, Clone)]
+pub struct BanOptions<'a> {
+    pub reason: Option<&'a str>,
+    /// Lifts the ban automatically once it has passed.
+    pub duration: Option<Duration>,
+    /// Overrides the guild's `ban-delete-messages` default.
+    pub delete_messages: Option<DeleteMessages>,
+    /// Overrides whether the guild's `ban-notify` settings DM the user first.
+    pub notify: Option<bool>,
+    /// Overrides whether the `ban-sync` setting bans them in every configured guild.
+    pub all_guilds: Option<bool>,
+    /// Also flags their Pulsar Link identities as banned, and bans any other Discord account found
+    /// for them. See [`PulsarApi::linked_users`](crate::api::PulsarApi::linked_users) for why
+    /// that's rarely any.
+    pub linked_accounts: bool,
+}
+
+/// A ban that went through.
+pub struct AppliedBan {
+    pub case: Option<Case>,
+    /// Why the ban's automatic unban may not behave as expected, when updating it failed.
+    pub schedule_problem: Option<&'static str>,
+}
+
+enum BanOutcome {
+    Banned {
+        name: String,
+        /// Whether the ban notice reached the user, if one was sent.
+        notified: Option<bool>,
+        applied: AppliedBan,
+    },
+    Refused(String),
+    /// Discord rejected the ban, with what the user was told about it.
+    Failed(String),
+}
+
+/// The DM sent to a user just before they're banned.
+fn ban_notice(guild: &PartialGuild, options: &BanOptions<'_>, appeal: Option<&str>) -> CreateEmbed {
+    let mut embed = notice(
+        format!("You have been banned from {}", guild.name),
+        options.reason,
+    )
+    .field(
+        "Duration",
+        options
+            .duration
+            .map(duration::format)
+            .unwrap_or_else(|| String::from("Permanent")),
+        false,
+    );
+
+    if let Some(appeal) = appeal {
+        embed = embed.field("Appeal", appeal, false);
+    }
+
+    embed
+}
+
+/// The case a ban is recorded as.
+fn ban_case(
+    guild_id: GuildId,
+    moderator_id: UserId,
+    target: UserId,
+    options: &BanOptions<'_>,
+    reason: Option<&str>,
+) -> NewCase {
+    NewCase {
+        guild_id: guild_id.get(),
+        action: CaseAction::Ban,
+        target_id: target.get(),
+        moderator_id: moderator_id.get(),
+        reason: reason.map(String::from),
+        duration: options.duration,
+    }
+}
+
+/// Bans a user in a guild and records it, with no checks of its own. Once Discord has applied
+/// the ban, failing to schedule its unban is logged and reported rather than returned.
+pub async fn apply_ban(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild_id: GuildId,
+    moderator_id: UserId,
+    target: UserId,
+    options: &BanOptions<'_>,
+    reason: Option<&str>,
+) -> Result<AppliedBan, CommandExecutionError> {
+    let schedule_problem = ban_and_schedule(
+        handler,
+        ctx,
+        guild_id,
+        moderator_id,
+        target,
+        options,
+        reason,
+    )
+    .await?;
+
+    let case = record_case(
+        handler,
+        ctx,
+        ban_case(guild_id, moderator_id, target, options, reason),
+    )
+    .await;
+
+    Ok(AppliedBan {
+        case,
+        schedule_problem,
+    })
+}
+
+/// The part of [`apply_ban`] before its case is recorded, returning the schedule problem to
+/// report, if any.
+async fn ban_and_schedule(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild_id: GuildId,
+    moderator_id: UserId,
+    target: UserId,
+    options: &BanOptions<'_>,
+    reason: Option<&str>,
+) -> Result<Option<&'static str>, CommandExecutionError> {
+    moderation::ban(
+        &ctx.http,
+        guild_id,
+        target,
+        delete_window(handler, guild_id, options.delete_messages).seconds(),
+        reason,
+    )
+    .await
+    .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    // A permanent ban replaces any earlier temporary one
+    let recorded = match options.duration {
+        Some(duration) => handler.db.insert_tempban(TempBan {
+            guild_id: guild_id.get(),
+            user_id: target.get(),
+            moderator_id: moderator_id.get(),
+            reason: reason.map(String::from),
+            unban_at: unix_now() + duration.as_secs(),
+        }),
+        None => handler
+            .db
+            .remove_tempban(guild_id.get(), target.get())
+            .map(|_| ()),
+    };
+
+    Ok(match (recorded, options.duration) {
+        (Ok(()), _) => None,
+        (Err(err), Some(_)) => {
+            error!(
+                "Banned {} in {} but failed to schedule the unban: {:?}",
+                target, guild_id, err
+            );
+            Some("The ban went through, but its unban couldn't be scheduled. Use `/unban` once it's over.")
+        }
+        (Err(err), None) => {
+            error!(
+                "Banned {} in {} but failed to cancel their temporary ban: {:?}",
+                target, guild_id, err
+            );
+            Some("The ban went through, but an earlier temporary ban couldn't be cancelled and may still lift it.")
+        }
+    })
+}
+
+/// Applies a ban made in `origin` to the other configured guilds, returning a line per guild.
+/// Guilds where the moderator couldn't ban the target themselves are skipped.
+async fn sync_ban(
+    handler: &BotEvents,
+    ctx: &Context,
+    origin: &PartialGuild,
+    moderator_id: UserId,
+    target: UserId,
+    options: &BanOptions<'_>,
+) -> Vec<String> {
+    let reason = synced_reason(origin, options.reason);
+    let mut results = vec![];
+
+    for guild_id in sync_guilds(handler, origin.id) {
+        let name = guild_name(ctx, guild_id);
+
+        let synced = match sync_guild(ctx, guild_id, moderator_id).await {
+            Ok(synced) => synced,
+            Err(skipped) => {
+                results.push(format!("❌ {}: {}", name, skipped));
+                continue;
             }
         };
 
-        // Target from command args
-        let target_command_data = match interaction.data.options.get(0) {
-            Some(target_command_data) => target_command_data,
-            None => {
-                return Err(Report::from(CommandExecutionError)
-                    .attach_printable("Failed to get command arg data user"))
+        if let Some(refusal) = synced.refusal(ctx, target, "ban").await {
+            results.push(format!("❌ {}: {}", name, refusal));
+            continue;
+        }
+
+        match apply_ban(
+            handler,
+            ctx,
+            guild_id,
+            moderator_id,
+            target,
+            options,
+            Some(&reason),
+        )
+        .await
+        {
+            Ok(applied) => {
+                let mut line = format!("✅ {}{}", name, describe_case(applied.case.as_ref()));
+
+                if let Some(problem) = applied.schedule_problem {
+                    line.push_str(&format!("\n⚠️ {}", problem));
+                }
+
+                results.push(line);
             }
-        };
+            Err(err) => {
+                warn!(
+                    "Failed to sync ban of {} to {}: {:?}",
+                    target, guild_id, err
+                );
+                results.push(format!("❌ {}: failed to ban", name));
+            }
+        }
+    }
+
+    results
+}
 
-        // Target from command args as user
-        let target_user = match target_command_data.value {
-            CommandDataOptionValue::User(user_id) => user_id,
-            _ => {
-                return Err(Report::from(CommandExecutionError)
-                    .attach_printable("Failed to get target user arg"))
+/// Bans a single user, who doesn't need to be in the guild, and records or clears their
+/// temporary ban. Members are subject to the usual hierarchy checks. If the ban fails after the
+/// notice went out, the user is sent a correction.
+async fn ban_target(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild: &PartialGuild,
+    moderator: &Member,
+    target: UserId,
+    options: &BanOptions<'_>,
+) -> Result<BanOutcome, CommandExecutionError> {
+    // Target to User from UserId
+    let target_user = target.to_user(&ctx.http).await.map_err(|e| {
+        Report::from(e)
+            .change_context(CommandExecutionError)
+            .attach_printable("Failed to fetch user")
+    })?;
+
+    // Users who already left have no roles to compare
+    let target_member = find_member(ctx, guild.id, target).await?;
+
+    if let Some(refusal) = refusal(guild, moderator, target, target_member.as_ref(), "ban") {
+        return Ok(BanOutcome::Refused(refusal));
+    }
+
+    let mut reserved = None;
+
+    // The notice has to go out first, once banned they no longer share a server with the bot
+    let notified = if should_notify(handler, guild.id, options.notify) {
+        let ban_notify = handler
+            .cfg
+            .guild(guild.id.get())
+            .map(|settings| &settings.ban_notify);
+        let mut notice = CreateMessage::new().embed(ban_notice(
+            guild,
+            options,
+            ban_notify.and_then(|ban_notify| ban_notify.appeal.as_deref()),
+        ));
+
+        if ban_notify.is_some_and(|ban_notify| ban_notify.appeal_channel.is_some()) {
+            // Opened ahead of the ban, so appeals are tied to its case
+            reserved = match handler.db.create_case(ban_case(
+                guild.id,
+                moderator.user.id,
+                target,
+                options,
+                options.reason,
+            )) {
+                Ok(case) => Some(case),
+                Err(err) => {
+                    error!("Failed to record ban case ahead of the notice: {:?}", err);
+                    None
+                }
+            };
+
+            notice = notice.components(vec![appeals::appeal_button(guild.id, reserved.as_ref())]);
+        }
+
+        Some(send_notice(ctx, target, notice).await)
+    } else {
+        None
+    };
+
+    let schedule_problem = match ban_and_schedule(
+        handler,
+        ctx,
+        guild.id,
+        moderator.user.id,
+        target,
+        options,
+        options.reason,
+    )
+    .await
+    {
+        Ok(schedule_problem) => schedule_problem,
+        Err(err) => {
+            error!("Failed to ban {} in {}: {:?}", target, guild.id, err);
+
+            if let Some(case) = &reserved {
+                if let Err(err) = handler.db.delete_case(guild.id.get(), case.id) {
+                    error!(
+                        "Failed to remove case #{} of the failed ban: {:?}",
+                        case.id, err
+                    );
+                }
             }
-        };
 
-        // Target to User from UserId
-        let target_user = target_user.to_user(&ctx.http).await.map_err(|e| {
-            Report::from(e)
-                .change_context(CommandExecutionError)
-                .attach_printable("Failed to fetch user")
-        })?;
-
-        // Get guild from command data
-        let guild = match interaction.guild_id {
-            Some(guild_id) => guild_id
-                .to_partial_guild(&ctx.http)
-                .await
-                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?,
-            None => return Err(Report::from(CommandExecutionError))?,
+            // They may already have been told they're banned, so correct that
+            let failure = retract_notice(ctx, target, &guild.name, notified, "ban").await;
+
+            return Ok(BanOutcome::Failed(failure));
+        }
+    };
+
+    let case = match reserved {
+        Some(case) => {
+            post_case(handler, ctx, &case).await;
+            Some(case)
+        }
+        None => {
+            record_case(
+                handler,
+                ctx,
+                ban_case(guild.id, moderator.user.id, target, options, options.reason),
+            )
+            .await
+        }
+    };
+
+    Ok(BanOutcome::Banned {
+        name: target_user.global_name.unwrap_or(target_user.name),
+        notified,
+        applied: AppliedBan {
+            case,
+            schedule_problem,
+        },
+    })
+}
+
+/// Bans `target` on behalf of the interaction's member and replies with the result. Refuses to
+/// ban the invoker themselves, the server owner, or anyone at or above the invoker's top role.
+/// With a `duration` the ban is recorded so the unban job lifts it once it expires, and with ban
+/// sync it's repeated in every other configured guild.
+pub async fn ban_user(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    target: UserId,
+    options: &BanOptions<'_>,
+) -> Result<(), CommandExecutionError> {
+    interaction
+        .defer_ephemeral(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+    let delete_messages = delete_window(handler, guild.id, options.delete_messages);
+
+    let (name, notified, schedule_problem) =
+        match ban_target(handler, ctx, &guild, interaction_member, target, options).await? {
+            BanOutcome::Banned {
+                name,
+                notified,
+                applied,
+            } => (
+                format!("{}{}", name, describe_case(applied.case.as_ref())),
+                notified,
+                applied.schedule_problem,
+            ),
+            BanOutcome::Refused(refusal) => return edit_reply(ctx, interaction, refusal).await,
+            BanOutcome::Failed(failure) => {
+                return edit_reply(ctx, interaction, format!("❌ <@{}>: {}", target, failure)).await
+            }
         };
 
-        if target_user.id == interaction.user.id {
-            let message = CreateInteractionResponseMessage::new()
-                .content("You're unable to ban yourself...")
-                .ephemeral(true);
+    let mut content = match options.duration {
+        Some(duration) => format!(
+            "Banned {} for {}{}{}",
+            name,
+            duration::format(duration),
+            describe_deletion(delete_messages),
+            describe_notice(notified, "ban")
+        ),
+        None => format!(
+            "Banned {}{}{}",
+            name,
+            describe_deletion(delete_messages),
+            describe_notice(notified, "ban")
+        ),
+    };
 
-            let builder = CreateInteractionResponse::Message(message);
+    if let Some(problem) = schedule_problem {
+        content.push_str(&format!("\n⚠️ {}", problem));
+    }
 
-            interaction
-                .create_response(&ctx.http, builder)
-                .await
-                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+    if syncs_bans(handler, options.all_guilds) {
+        let results = sync_ban(
+            handler,
+            ctx,
+            &guild,
+            interaction_member.user.id,
+            target,
+            options,
+        )
+        .await;
 
-            return Ok(());
+        if !results.is_empty() {
+            content.push_str("\n\nOther servers:\n");
+            content.push_str(&results.join("\n"));
         }
+    }
 
-        if guild.owner_id == target_user.id {
-            let message = CreateInteractionResponseMessage::new()
-                .content("Unable to ban server owner")
-                .ephemeral(true);
+    if options.linked_accounts {
+        content.push_str("\n\n");
+        content.push_str(
+            &ban_linked_accounts(handler, ctx, &guild, interaction_member, target, options).await,
+        );
+    }
 
-            let builder = CreateInteractionResponse::Message(message);
+    // respond to command
+    edit_reply(ctx, interaction, content).await
+}
 
-            interaction
-                .create_response(&ctx.http, builder)
-                .await
-                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+/// Bans the other Discord accounts the backend returns for `target`'s Steam or Gmodstore identity,
+/// then flags their Pulsar Link users as banned so those identities can't simply be linked again.
+/// Alts that never linked, or linked other identities, aren't found. Returns a summary for the
+/// ban reply.
+async fn ban_linked_accounts(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild: &PartialGuild,
+    moderator: &Member,
+    target: UserId,
+    options: &BanOptions<'_>,
+) -> String {
+    let users = match handler.api.user_by_discord(target).await {
+        Ok(Some(user)) => handler.api.linked_users(&user).await,
+        Ok(None) => {
+            return String::from(
+                "They aren't linked to Pulsar Link, so no other accounts were banned",
+            )
+        }
+        Err(err) => Err(err),
+    };
 
-            return Ok(());
+    let users = match users {
+        Ok(users) => users,
+        Err(err) => {
+            warn!("Failed to look up linked accounts of {}: {:?}", target, err);
+            return String::from("Couldn't look up their linked accounts, please try again later");
         }
+    };
 
-        // Target from command args as member
-        let target_member = guild
-            .member(&ctx.http, &target_user)
-            .await
-            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+    let mut lines = vec![];
+    let alt_reason = format!(
+        "Linked account of {}: {}",
+        target,
+        options.reason.unwrap_or("No reason given")
+    );
+    let alt_options = BanOptions {
+        reason: Some(&alt_reason),
+        ..options.clone()
+    };
 
-        if let Some((_, target_role_pos)) = target_member.highest_role_info(&ctx.cache) {
-            if let Some((_, member_role_pos)) = interaction_member.highest_role_info(&ctx.cache) {
-                if target_role_pos >= member_role_pos
-                    && (guild.owner_id != interaction_member.user.id)
-                {
-                    let message = CreateInteractionResponseMessage::new()
-                        .content("You lack sufficient privileges to ban this user")
-                        .ephemeral(true);
+    let alts = users
+        .iter()
+        .filter_map(|user| user.discord_id)
+        .map(UserId::new)
+        .filter(|alt| *alt != target);
+
+    for alt in alts {
+        match ban_target(handler, ctx, guild, moderator, alt, &alt_options).await {
+            Ok(BanOutcome::Banned { applied, .. }) => {
+                let mut line = format!("✅ <@{}>{}", alt, describe_case(applied.case.as_ref()));
 
-                    let builder = CreateInteractionResponse::Message(message);
+                if let Some(problem) = applied.schedule_problem {
+                    line.push_str(&format!(" ⚠️ {}", problem));
+                }
 
-                    interaction
-                        .create_response(&ctx.http, builder)
-                        .await
-                        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+                if syncs_bans(handler, options.all_guilds) {
+                    let results =
+                        sync_ban(handler, ctx, guild, moderator.user.id, alt, &alt_options).await;
+                    let synced = results
+                        .iter()
+                        .filter(|result| result.starts_with('✅'))
+                        .count();
 
-                    return Ok(());
+                    line.push_str(&format!(
+                        ", and in {} of {} other servers",
+                        synced,
+                        results.len()
+                    ));
                 }
+
+                lines.push(line);
             }
-        };
+            Ok(BanOutcome::Refused(refusal)) => lines.push(format!("❌ <@{}>: {}", alt, refusal)),
+            Ok(BanOutcome::Failed(failure)) => lines.push(format!("❌ <@{}>: {}", alt, failure)),
+            Err(err) => {
+                warn!("Failed to ban linked account {}: {:?}", alt, err);
+                lines.push(format!("❌ <@{}>: failed to ban", alt));
+            }
+        }
+    }
+
+    let mut summary = if lines.is_empty() {
+        String::from(
+            "Pulsar Link returned no other Discord accounts for their identities, so alts that never linked them weren't banned",
+        )
+    } else {
+        format!("Linked accounts:\n{}", lines.join("\n"))
+    };
+
+    let mut flagged = 0;
+
+    for pulsar_id in users.iter().filter_map(|user| user.id.as_deref()) {
+        match handler.api.flag_banned(pulsar_id, options.reason).await {
+            Ok(()) => flagged += 1,
+            Err(err) => warn!(
+                "Failed to flag Pulsar Link user {} as banned: {:?}",
+                pulsar_id, err
+            ),
+        }
+    }
+
+    summary.push_str(&format!(
+        "\nFlagged {} of {} Pulsar Link users as banned",
+        flagged,
+        users.len()
+    ));
 
-        // ban user
-        match interaction.data.options.get(1) {
-            Some(target_command_data) => {
-                let ban_reason = match &target_command_data.value {
-                    CommandDataOptionValue::String(ban_reason) => ban_reason,
-                    _ => {
-                        return Err(Report::from(CommandExecutionError)
-                            .attach_printable("Failed to get ban reason arg"))
+    summary
+}
+
+/// Bans every user in `targets`, whether or not they're in the guild, and replies with a summary.
+async fn hackban(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    targets: Vec<UserId>,
+    invalid: Vec<String>,
+    options: &BanOptions<'_>,
+) -> Result<(), CommandExecutionError> {
+    interaction
+        .defer_ephemeral(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+    let delete_messages = delete_window(handler, guild.id, options.delete_messages);
+
+    let mut banned = vec![];
+    let mut notices = 0;
+    let mut notices_delivered = 0;
+    let mut problems = vec![];
+
+    for target in &targets {
+        match ban_target(handler, ctx, &guild, interaction_member, *target, options).await {
+            Ok(BanOutcome::Banned {
+                notified, applied, ..
+            }) => {
+                banned.push(*target);
+
+                if let Some(problem) = applied.schedule_problem {
+                    problems.push(format!("`{}`: {}", target, problem));
+                }
+
+                if let Some(delivered) = notified {
+                    notices += 1;
+
+                    if delivered {
+                        notices_delivered += 1;
                     }
-                };
+                }
+            }
+            Ok(BanOutcome::Refused(refusal)) => problems.push(format!("`{}`: {}", target, refusal)),
+            Ok(BanOutcome::Failed(failure)) => problems.push(format!("`{}`: {}", target, failure)),
+            Err(err) => {
+                warn!("Failed to hackban {}: {:?}", target, err);
+                problems.push(format!("`{}`: failed to ban", target));
+            }
+        }
+    }
 
-                guild
-                    .ban_with_reason(&ctx.http, &target_user, 0, ban_reason)
-                    .await
-                    .map_err(|e| Report::from(e).change_context(CommandExecutionError))?
+    let mut content = format!("Banned {} of {} users", banned.len(), targets.len());
+
+    if let Some(duration) = options.duration {
+        content.push_str(&format!(" for {}", duration::format(duration)));
+    }
+
+    if !banned.is_empty() {
+        content.push_str(describe_deletion(delete_messages));
+    }
+
+    if notices > 0 {
+        content.push_str(&format!(
+            "\nSent a DM about the ban to {} of {} users, the rest have their DMs closed",
+            notices_delivered, notices
+        ));
+    }
+
+    if !invalid.is_empty() {
+        content.push_str(&format!(
+            "\nIgnored {} entries that aren't user IDs",
+            invalid.len()
+        ));
+    }
+
+    for problem in problems.iter().take(REPORTED_PROBLEMS) {
+        content.push('\n');
+        content.push_str(problem);
+    }
+
+    if problems.len() > REPORTED_PROBLEMS {
+        content.push_str(&format!(
+            "\n...and {} more",
+            problems.len() - REPORTED_PROBLEMS
+        ));
+    }
+
+    // Only users banned here are synced, and each is checked again in every other guild
+    if syncs_bans(handler, options.all_guilds) && !banned.is_empty() {
+        let reason = synced_reason(&guild, options.reason);
+        let mut results = vec![];
+
+        for guild_id in sync_guilds(handler, guild.id) {
+            let name = guild_name(ctx, guild_id);
+
+            let sync_target = match sync_guild(ctx, guild_id, interaction_member.user.id).await {
+                Ok(sync_target) => sync_target,
+                Err(skipped) => {
+                    results.push(format!("❌ {}: {}", name, skipped));
+                    continue;
+                }
+            };
+
+            let mut synced = 0;
+            let mut refused = 0;
+
+            for target in &banned {
+                if sync_target.refusal(ctx, *target, "ban").await.is_some() {
+                    refused += 1;
+                    continue;
+                }
+
+                match apply_ban(
+                    handler,
+                    ctx,
+                    guild_id,
+                    interaction_member.user.id,
+                    *target,
+                    options,
+                    Some(&reason),
+                )
+                .await
+                {
+                    Ok(_) => synced += 1,
+                    Err(err) => {
+                        warn!(
+                            "Failed to sync ban of {} to {}: {:?}",
+                            target, guild_id, err
+                        )
+                    }
+                }
             }
-            None => {
-                guild
-                    .ban(&ctx.http, &target_user, 0)
-                    .await
-                    .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+            let mut line = format!(
+                "{} {}: banned {} of {}",
+                if synced == banned.len() { "✅" } else { "❌" },
+                name,
+                synced,
+                banned.len()
+            );
+
+            if refused > 0 {
+                line.push_str(&format!(
+                    ", {} refused by the hierarchy checks there",
+                    refused
+                ));
             }
-        };
 
-        // respond to command
-        let message = CreateInteractionResponseMessage::new()
-            .content(format!(
-                "Banned {}",
-                target_user.global_name.unwrap_or(target_user.name)
-            ))
-            .ephemeral(true);
-        let interaction_response = CreateInteractionResponse::Message(message);
+            results.push(line);
+        }
 
-        interaction
-            .create_response(&ctx.http, interaction_response)
-            .await
-            .map_err(|e| {
-                Report::from(e)
-                    .change_context(CommandExecutionError)
-                    .attach_printable("Failed to reply to message")
-            })?;
+        if !results.is_empty() {
+            content.push_str("\n\nOther servers:\n");
+            content.push_str(&results.join("\n"));
+        }
+    }
 
-        Ok(())
+    edit_reply(ctx, interaction, content).await
+}
+
+#[async_trait]
+impl Command for BanCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut target = None;
+        let mut duration_arg = None;
+        let mut ids_arg = None;
+        let mut file_arg = None;
+        let mut delete_arg = None;
+        let mut options = BanOptions::default();
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::User(user_id)) => target = Some(*user_id),
+                ("reason", CommandDataOptionValue::String(ban_reason)) => {
+                    options.reason = Some(ban_reason.as_str())
+                }
+                ("duration", CommandDataOptionValue::String(duration)) => {
+                    duration_arg = Some(duration.as_str())
+                }
+                ("ids", CommandDataOptionValue::String(ids)) => ids_arg = Some(ids.as_str()),
+                ("notify", CommandDataOptionValue::Boolean(notify)) => {
+                    options.notify = Some(*notify)
+                }
+                ("all_guilds", CommandDataOptionValue::Boolean(all_guilds)) => {
+                    options.all_guilds = Some(*all_guilds)
+                }
+                ("linked_accounts", CommandDataOptionValue::Boolean(linked_accounts)) => {
+                    options.linked_accounts = *linked_accounts
+                }
+                ("delete_messages", CommandDataOptionValue::String(window)) => {
+                    delete_arg = Some(window.as_str())
+                }
+                ("file", CommandDataOptionValue::Attachment(attachment_id)) => {
+                    file_arg = interaction.data.resolved.attachments.get(attachment_id)
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(input) = duration_arg {
+            match duration::parse(input) {
+                Some(duration) => options.duration = Some(duration),
+                None => {
+                    return reply_ephemeral(
+                        ctx,
+                        interaction,
+                        format!(
+                            "`{}` isn't a valid duration. Use something like `7d`, `12h` or `1d12h`.",
+                            input
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        if let Some(input) = delete_arg {
+            match DeleteMessages::parse(input) {
+                Some(window) => options.delete_messages = Some(window),
+                None => {
+                    return reply_ephemeral(
+                        ctx,
+                        interaction,
+                        format!(
+                            "`{}` isn't a valid message deletion window. Use `none`, `1h`, `24h` or `7d`.",
+                            input
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        if ids_arg.is_none() && file_arg.is_none() {
+            return match target {
+                Some(target) => ban_user(handler, ctx, interaction, target, &options).await,
+                None => {
+                    reply_ephemeral(
+                        ctx,
+                        interaction,
+                        "Give a user, a list of user IDs, or a file of user IDs to ban",
+                    )
+                    .await
+                }
+            };
+        }
+
+        // Hackban: everything given is banned in one go
+        let mut list = target.map(|target| target.to_string()).unwrap_or_default();
+
+        if let Some(ids) = ids_arg {
+            list.push('\n');
+            list.push_str(ids);
+        }
+
+        if let Some(file) = file_arg {
+            let Some(contents) = download_id_list(file).await? else {
+                return reply_ephemeral(ctx, interaction, id_list_too_large(file)).await;
+            };
+
+            list.push('\n');
+            list.push_str(&contents);
+        }
+
+        let (targets, invalid) = parse_user_ids(&list);
+
+        if targets.is_empty() {
+            return reply_ephemeral(ctx, interaction, "No user IDs were given").await;
+        }
+
+        if targets.len() > MAX_HACKBAN_TARGETS {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!(
+                    "That's {} users, at most {} can be banned at once",
+                    targets.len(),
+                    MAX_HACKBAN_TARGETS
+                ),
+            )
+            .await;
+        }
+
+        hackban(handler, ctx, interaction, targets, invalid, &options).await
+    }
+
+    async fn component(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &mut ComponentInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        appeals::component(handler, ctx, interaction).await
+    }
+
+    async fn modal(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &ModalInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        appeals::modal(handler, ctx, interaction).await
     }
 
     async fn register(&self, _: &BotEvents) -> CreateCommand {
         return CreateCommand::new(self.name())
             .description(self.description())
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::User, "user", "The user to ban")
-                    .required(true),
-            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "The user to ban, who doesn't need to be in the server",
+            ))
             .add_option(CreateCommandOption::new(
                 CommandOptionType::String,
                 "reason",
                 "Reason to ban the user",
             ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "duration",
+                "Lift the ban automatically after this long, e.g. 7d or 12h",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "delete_messages",
+                    "How much of their recent message history to delete",
+                )
+                .add_string_choice("None", "none")
+                .add_string_choice("Last hour", "1h")
+                .add_string_choice("Last 24 hours", "24h")
+                .add_string_choice("Last 7 days", "7d"),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "notify",
+                "DM the user about the ban first, defaults to the server's setting",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "all_guilds",
+                "Ban them in every partner server too, defaults to the ban sync setting",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "linked_accounts",
+                "Also flag their Steam and Gmodstore accounts as banned in Pulsar Link",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "ids",
+                "Several user IDs to ban at once, separated by spaces or commas",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Attachment,
+                "file",
+                "A text file of user IDs to ban at once",
+            ))
             .default_member_permissions(Permissions::BAN_MEMBERS)
             .dm_permission(false);
     }
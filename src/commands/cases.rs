@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result, ResultExt};
+use serenity::{
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        Permissions,
+    },
+};
+
+use crate::{event_handler::BotEvents, mod_log::case_embed};
+
+use super::{
+    moderation::{reply_ephemeral, truncate},
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Most cases listed by `/cases` before the rest are summarised.
+const LISTED_CASES: usize = 20;
+
+/// Longest reason shown per case in `/cases`, in characters.
+const LISTED_REASON_LENGTH: usize = 100;
+
+/// Discord caps embed descriptions at 4096 characters. Some room is left for the user mention and
+/// the line summarising the rest.
+const DESCRIPTION_LIMIT: usize = 4000;
+
+#[derive(Debug)]
+pub struct CasesCommand;
+
+impl CommandInfo for CasesCommand {
+    fn name(&self) -> String {
+        String::from("cases")
+    }
+
+    fn description(&self) -> String {
+        String::from("List a user's moderation history")
+    }
+}
+
+#[async_trait]
+impl Command for CasesCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Command used outside a guild"));
+        };
+
+        let target = interaction
+            .data
+            .options
+            .iter()
+            .find_map(|option| match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::User(user_id)) => Some(*user_id),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                Report::from(CommandExecutionError).attach_printable("Failed to get user arg")
+            })?;
+
+        let cases = handler
+            .db
+            .cases_for(guild_id.get(), target.get())
+            .change_context(CommandExecutionError)?;
+
+        if cases.is_empty() {
+            return reply_ephemeral(ctx, interaction, format!("<@{}> has no cases", target)).await;
+        }
+
+        let mut description = String::new();
+        let mut listed = 0;
+
+        for case in cases.iter().take(LISTED_CASES) {
+            let line = format!(
+                "**#{}** {} <t:{}:d> by <@{}>: {}\n",
+                case.id,
+                case.action,
+                case.created_at,
+                case.moderator_id,
+                truncate(
+                    case.reason.as_deref().unwrap_or("No reason given"),
+                    LISTED_REASON_LENGTH
+                )
+            );
+
+            if description.chars().count() + line.chars().count() > DESCRIPTION_LIMIT {
+                break;
+            }
+
+            description.push_str(&line);
+            listed += 1;
+        }
+
+        if cases.len() > listed {
+            description.push_str(&format!("...and {} older", cases.len() - listed));
+        }
+
+        let embed = CreateEmbed::new()
+            .title(format!("{} cases", cases.len()))
+            .description(format!("<@{}>\n\n{}", target, description.trim_end()));
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .embed(embed)
+                        .ephemeral(true),
+                ),
+            )
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::User,
+                    "user",
+                    "The user to list cases for",
+                )
+                .required(true),
+            )
+            .default_member_permissions(Permissions::MODERATE_MEMBERS)
+            .dm_permission(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct CaseCommand;
+
+impl CommandInfo for CaseCommand {
+    fn name(&self) -> String {
+        String::from("case")
+    }
+
+    fn description(&self) -> String {
+        String::from("Show a moderation case or change its reason")
+    }
+}
+
+#[async_trait]
+impl Command for CaseCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Command used outside a guild"));
+        };
+
+        let mut id = None;
+        let mut reason = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("id", CommandDataOptionValue::Integer(case_id)) => id = Some(*case_id),
+                ("reason", CommandDataOptionValue::String(case_reason)) => {
+                    reason = Some(case_reason.as_str())
+                }
+                _ => {}
+            }
+        }
+
+        let Some(id) = id else {
+            return Err(
+                Report::from(CommandExecutionError).attach_printable("Failed to get id arg")
+            );
+        };
+
+        let case = match reason {
+            Some(reason) => handler.db.update_case_reason(guild_id.get(), id, reason),
+            None => handler.db.case(guild_id.get(), id),
+        }
+        .change_context(CommandExecutionError)?;
+
+        let Some(case) = case else {
+            return reply_ephemeral(ctx, interaction, format!("There's no case #{}", id)).await;
+        };
+
+        let mut message = CreateInteractionResponseMessage::new()
+            .embed(case_embed(&case))
+            .ephemeral(true);
+
+        if reason.is_some() {
+            message = message.content(format!("Updated the reason of case #{}", case.id));
+        }
+
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "id", "The case number")
+                    .min_int_value(1)
+                    .required(true),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "reason",
+                "Replace the case's reason",
+            ))
+            .default_member_permissions(Permissions::MODERATE_MEMBERS)
+            .dm_permission(false)
+    }
+}
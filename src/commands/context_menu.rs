@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::CreateCommand,
+    client::Context,
+    model::{
+        application::{CommandInteraction, CommandType, ResolvedTarget},
+        id::UserId,
+        Permissions,
+    },
+};
+
+use crate::{api::UserLookup, event_handler::BotEvents};
+
+use super::{
+    addons::describe_addons,
+    ban::{ban_user, BanOptions},
+    response::respond_lookup,
+    user_get::describe_user,
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// The user a context menu was opened on, or the author of the message it was opened on.
+fn target_user(interaction: &CommandInteraction) -> Result<UserId, CommandExecutionError> {
+    match interaction.data.target() {
+        Some(ResolvedTarget::User(user, _)) => Ok(user.id),
+        Some(ResolvedTarget::Message(message)) => Ok(message.author.id),
+        _ => Err(Report::from(CommandExecutionError)
+            .attach_printable("Failed to get context menu target")),
+    }
+}
+
+#[derive(Debug)]
+pub struct LookupUserContextCommand;
+
+impl CommandInfo for LookupUserContextCommand {
+    fn name(&self) -> String {
+        String::from("Lookup Pulsar Link")
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn kind(&self) -> CommandType {
+        CommandType::User
+    }
+}
+
+#[async_trait]
+impl Command for LookupUserContextCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let target = target_user(interaction)?;
+        let message = describe_user(handler, UserLookup::Discord(target)).await;
+
+        respond_lookup(handler, ctx, interaction, message).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .kind(self.kind())
+            .dm_permission(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct ViewAddonsContextCommand;
+
+impl CommandInfo for ViewAddonsContextCommand {
+    fn name(&self) -> String {
+        String::from("View Addons")
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn kind(&self) -> CommandType {
+        CommandType::User
+    }
+}
+
+#[async_trait]
+impl Command for ViewAddonsContextCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let target = target_user(interaction)?;
+        let message = describe_addons(handler, UserLookup::Discord(target)).await;
+
+        respond_lookup(handler, ctx, interaction, message).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .kind(self.kind())
+            .dm_permission(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct BanContextCommand;
+
+impl CommandInfo for BanContextCommand {
+    fn name(&self) -> String {
+        String::from("Ban")
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn kind(&self) -> CommandType {
+        CommandType::User
+    }
+}
+
+#[async_trait]
+impl Command for BanContextCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let target = target_user(interaction)?;
+
+        ban_user(handler, ctx, interaction, target, &BanOptions::default()).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .kind(self.kind())
+            .default_member_permissions(Permissions::BAN_MEMBERS)
+            .dm_permission(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct LookupAuthorContextCommand;
+
+impl CommandInfo for LookupAuthorContextCommand {
+    fn name(&self) -> String {
+        String::from("Lookup author")
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn kind(&self) -> CommandType {
+        CommandType::Message
+    }
+}
+
+#[async_trait]
+impl Command for LookupAuthorContextCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let target = target_user(interaction)?;
+        let message = describe_user(handler, UserLookup::Discord(target)).await;
+
+        respond_lookup(handler, ctx, interaction, message).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .kind(self.kind())
+            .dm_permission(false)
+    }
+}
@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        Permissions,
+    },
+};
+use tracing::error;
+
+use crate::{
+    database::cases::{CaseAction, NewCase},
+    event_handler::BotEvents,
+};
+
+use super::{
+    moderation::{
+        describe_case, describe_notice, edit_reply, find_member, moderation_context, notice,
+        notify, record_case, refusal, reply_ephemeral, retract_notice, should_notify,
+    },
+    Command, CommandExecutionError, CommandInfo,
+};
+
+#[derive(Debug)]
+pub struct KickCommand;
+
+impl CommandInfo for KickCommand {
+    fn name(&self) -> String {
+        String::from("kick")
+    }
+
+    fn description(&self) -> String {
+        String::from("Kick a user from the server")
+    }
+}
+
+#[async_trait]
+impl Command for KickCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut target = None;
+        let mut reason = None;
+        let mut notify_arg = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::User(user_id)) => target = Some(*user_id),
+                ("reason", CommandDataOptionValue::String(kick_reason)) => {
+                    reason = Some(kick_reason.as_str())
+                }
+                ("notify", CommandDataOptionValue::Boolean(notify)) => notify_arg = Some(*notify),
+                _ => {}
+            }
+        }
+
+        let Some(target) = target else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Failed to get target user arg"));
+        };
+
+        let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+
+        let Some(target_member) = find_member(ctx, guild.id, target).await? else {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!("<@{}> isn't in this server", target),
+            )
+            .await;
+        };
+
+        if let Some(refusal) = refusal(
+            &guild,
+            interaction_member,
+            target,
+            Some(&target_member),
+            "kick",
+        ) {
+            return reply_ephemeral(ctx, interaction, refusal).await;
+        }
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        // Sent first, once kicked they may no longer share a server with the bot
+        let notified = if should_notify(handler, guild.id, notify_arg) {
+            let notice = notice(format!("You have been kicked from {}", guild.name), reason);
+
+            Some(notify(ctx, target, notice).await)
+        } else {
+            None
+        };
+
+        let kicked = match reason {
+            Some(reason) => guild.id.kick_with_reason(&ctx.http, target, reason).await,
+            None => guild.id.kick(&ctx.http, target).await,
+        };
+
+        if let Err(err) = kicked {
+            error!("Failed to kick {} in {}: {:?}", target, guild.id, err);
+
+            let failure = retract_notice(ctx, target, &guild.name, notified, "kick").await;
+
+            return edit_reply(ctx, interaction, format!("❌ <@{}>: {}", target, failure)).await;
+        }
+
+        let case = record_case(
+            handler,
+            ctx,
+            NewCase {
+                guild_id: guild.id.get(),
+                action: CaseAction::Kick,
+                target_id: target.get(),
+                moderator_id: interaction.user.id.get(),
+                reason: reason.map(String::from),
+                duration: None,
+            },
+        )
+        .await;
+
+        let user = &target_member.user;
+
+        edit_reply(
+            ctx,
+            interaction,
+            format!(
+                "Kicked {}{}{}",
+                user.global_name.as_deref().unwrap_or(&user.name),
+                describe_case(case.as_ref()),
+                describe_notice(notified, "kick")
+            ),
+        )
+        .await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::User, "user", "The user to kick")
+                    .required(true),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "reason",
+                "Reason for the kick",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "notify",
+                "DM the user about the kick first, defaults to the server's setting",
+            ))
+            .default_member_permissions(Permissions::KICK_MEMBERS)
+            .dm_permission(false)
+    }
+}
@@ -0,0 +1,263 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{CreateCommand, CreateCommandOption, CreateEmbed, CreateMessage},
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        channel::ChannelType,
+        id::ChannelId,
+        Permissions,
+    },
+};
+use tracing::{error, warn};
+
+use crate::{event_handler::BotEvents, lockdown};
+
+use super::{
+    moderation::{channel_permissions, edit_reply, moderation_context, reply_ephemeral},
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Permissions the invoker needs in each channel they lock or unlock.
+const REQUIRED_PERMISSIONS: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::MANAGE_CHANNELS);
+
+#[derive(Debug)]
+pub struct LockdownCommand;
+
+impl CommandInfo for LockdownCommand {
+    fn name(&self) -> String {
+        String::from("lockdown")
+    }
+
+    fn description(&self) -> String {
+        String::from("Stop members from sending messages in a channel")
+    }
+}
+
+#[derive(Debug)]
+pub struct UnlockCommand;
+
+impl CommandInfo for UnlockCommand {
+    fn name(&self) -> String {
+        String::from("unlock")
+    }
+
+    fn description(&self) -> String {
+        String::from("Let members send messages in a locked channel again")
+    }
+}
+
+/// The message posted in a channel once it's locked or unlocked.
+fn channel_notice(locked: bool, reason: Option<&str>) -> CreateEmbed {
+    let mut embed = if locked {
+        CreateEmbed::new()
+            .title("🔒 Channel locked")
+            .description("Only staff can send messages here for now.")
+    } else {
+        CreateEmbed::new()
+            .title("🔓 Channel unlocked")
+            .description("You can send messages here again.")
+    };
+
+    if let Some(reason) = reason {
+        embed = embed.field("Reason", reason, false);
+    }
+
+    embed
+}
+
+/// Locks or unlocks the chosen channel, or every lockdown channel with `all`, and replies with the
+/// result. Channels the invoker can't manage are left alone, and channels that change get a notice
+/// posted in them.
+async fn set_locked(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    locked: bool,
+) -> Result<(), CommandExecutionError> {
+    let mut channel = interaction.channel_id;
+    let mut all = false;
+    let mut reason = None;
+
+    for option in &interaction.data.options {
+        match (option.name.as_str(), &option.value) {
+            ("channel", CommandDataOptionValue::Channel(channel_id)) => channel = *channel_id,
+            ("all", CommandDataOptionValue::Boolean(value)) => all = *value,
+            ("reason", CommandDataOptionValue::String(value)) => reason = Some(value.as_str()),
+            _ => {}
+        }
+    }
+
+    let (guild, member) = moderation_context(ctx, interaction).await?;
+
+    let settings = handler
+        .cfg
+        .guild(guild.id.get())
+        .map(|settings| settings.lockdown.clone())
+        .unwrap_or_default();
+
+    let channels = if all {
+        settings
+            .channels
+            .iter()
+            .copied()
+            .map(ChannelId::new)
+            .collect()
+    } else {
+        vec![channel]
+    };
+
+    if channels.is_empty() {
+        return reply_ephemeral(
+            ctx,
+            interaction,
+            "No lockdown channels are configured for this server",
+        )
+        .await;
+    }
+
+    interaction
+        .defer_ephemeral(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let role = settings.role(guild.id);
+    let (action, state) = if locked {
+        ("Locked", "locked")
+    } else {
+        ("Unlocked", "unlocked")
+    };
+
+    let mut changed = vec![];
+    let mut unchanged = vec![];
+    let mut forbidden = vec![];
+    let mut failed = vec![];
+
+    for channel in channels {
+        match channel_permissions(ctx, &guild, member, channel).await {
+            Ok(permissions) if permissions.contains(REQUIRED_PERMISSIONS) => {}
+            Ok(_) => {
+                forbidden.push(format!("<#{}>", channel));
+                continue;
+            }
+            Err(err) => {
+                error!("Failed to check permissions in {}: {:?}", channel, err);
+                failed.push(format!("<#{}>", channel));
+                continue;
+            }
+        }
+
+        match lockdown::set_locked(&ctx.http, &handler.db, channel, role, locked).await {
+            Ok(true) => {
+                changed.push(format!("<#{}>", channel));
+
+                if let Err(err) = channel
+                    .send_message(
+                        &ctx.http,
+                        CreateMessage::new().embed(channel_notice(locked, reason)),
+                    )
+                    .await
+                {
+                    warn!("Failed to post {} notice in {}: {:?}", state, channel, err);
+                }
+            }
+            Ok(false) => unchanged.push(format!("<#{}>", channel)),
+            Err(err) => {
+                error!("Failed to set {} {}: {:?}", channel, state, err);
+                failed.push(format!("<#{}>", channel));
+            }
+        }
+    }
+
+    let mut lines = vec![];
+
+    if !changed.is_empty() {
+        lines.push(format!("{} {}", action, changed.join(", ")));
+    }
+
+    if !unchanged.is_empty() {
+        lines.push(format!("Already {}: {}", state, unchanged.join(", ")));
+    }
+
+    if !forbidden.is_empty() {
+        lines.push(format!(
+            "Skipped {}, you need the View Channel and Manage Channels permissions there",
+            forbidden.join(", ")
+        ));
+    }
+
+    if !failed.is_empty() {
+        lines.push(format!("Failed to update {}", failed.join(", ")));
+    }
+
+    edit_reply(ctx, interaction, lines.join("\n")).await
+}
+
+fn register(command: CreateCommand, action: &str) -> CreateCommand {
+    command
+        .add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Channel,
+                "channel",
+                format!("The channel to {}, defaults to this one", action),
+            )
+            .channel_types(vec![
+                ChannelType::Text,
+                ChannelType::News,
+                ChannelType::Voice,
+                ChannelType::Forum,
+            ]),
+        )
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "all",
+            "Use every lockdown channel configured for the server instead",
+        ))
+        .add_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "reason",
+            "Reason shown in the notice posted to the channel",
+        ))
+        .default_member_permissions(Permissions::MANAGE_CHANNELS)
+        .dm_permission(false)
+}
+
+#[async_trait]
+impl Command for LockdownCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        set_locked(handler, ctx, interaction, true).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        register(
+            CreateCommand::new(self.name()).description(self.description()),
+            "lock",
+        )
+    }
+}
+
+#[async_trait]
+impl Command for UnlockCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        set_locked(handler, ctx, interaction, false).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        register(
+            CreateCommand::new(self.name()).description(self.description()),
+            "unlock",
+        )
+    }
+}
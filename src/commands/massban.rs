@@ -0,0 +1,785 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+        CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    },
+    client::Context,
+    model::{
+        application::{
+            ButtonStyle, CommandDataOptionValue, CommandInteraction, CommandOptionType,
+            ComponentInteraction,
+        },
+        guild::{Member, PartialGuild},
+        id::{GuildId, UserId},
+        Permissions,
+    },
+};
+use tokio::time;
+use tracing::warn;
+
+use crate::{
+    config::DeleteMessages,
+    duration::{self, unix_now},
+    event_handler::BotEvents,
+};
+
+use super::{
+    ban::{apply_ban, delete_window, describe_deletion, BanOptions},
+    moderation::{
+        download_id_list, edit_reply, id_list_too_large, moderation_context, parse_user_ids,
+        refusal, reply_ephemeral,
+    },
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Most users a single mass ban will go through.
+const MAX_MASSBAN_TARGETS: usize = 500;
+
+/// Users listed individually in the preview.
+const PREVIEWED_TARGETS: usize = 25;
+
+/// Skipped or failed users listed individually in a summary.
+const REPORTED_PROBLEMS: usize = 15;
+
+/// Bans made between progress updates.
+const BATCH_SIZE: usize = 10;
+
+/// Pause between batches, leaving room in the ban rate limit for other moderation.
+const BATCH_PAUSE: Duration = Duration::from_secs(1);
+
+/// How long a preview can be confirmed for, interaction tokens expire after 15 minutes anyway.
+const CONFIRM_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// How long a raid alert's flagged accounts can be mass banned for.
+const ALERT_CONFIRM_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// A previewed mass ban waiting to be confirmed, keyed by the ID of the interaction that made it.
+#[derive(Debug, Clone)]
+pub struct PendingMassban {
+    pub guild_id: GuildId,
+    /// The moderator who previewed it, or `None` for a raid alert, which anyone allowed to ban
+    /// members can confirm.
+    pub moderator_id: Option<UserId>,
+    pub targets: Vec<UserId>,
+    pub reason: Option<String>,
+    pub delete_messages: Option<DeleteMessages>,
+    created: Instant,
+}
+
+impl PendingMassban {
+    pub fn new(
+        guild_id: GuildId,
+        moderator_id: Option<UserId>,
+        targets: Vec<UserId>,
+        reason: Option<String>,
+        delete_messages: Option<DeleteMessages>,
+    ) -> Self {
+        Self {
+            guild_id,
+            moderator_id,
+            targets,
+            reason,
+            delete_messages,
+            created: Instant::now(),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        let window = match self.moderator_id {
+            Some(_) => CONFIRM_WINDOW,
+            None => ALERT_CONFIRM_WINDOW,
+        };
+
+        self.created.elapsed() >= window
+    }
+
+    /// Why the user who pressed a button may not handle this mass ban, if they may not.
+    fn refusal(&self, interaction: &ComponentInteraction) -> Option<&'static str> {
+        match self.moderator_id {
+            Some(moderator_id) if moderator_id != interaction.user.id => {
+                Some("Only the moderator who started this mass ban can confirm or cancel it")
+            }
+            None if !interaction
+                .member
+                .as_ref()
+                .and_then(|member| member.permissions)
+                .is_some_and(|permissions| permissions.ban_members()) =>
+            {
+                Some("You need the Ban Members permission to handle raid alerts")
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Which members `/massban` selects, every given filter has to match.
+#[derive(Debug, Default)]
+struct MemberFilter {
+    /// Unix timestamp members must have joined after.
+    joined_after: Option<i64>,
+    /// Unix timestamp accounts must have been created after.
+    created_after: Option<i64>,
+    /// Lowercase wildcard pattern matched against usernames, display names and nicknames.
+    name: Option<String>,
+}
+
+impl MemberFilter {
+    fn is_empty(&self) -> bool {
+        self.joined_after.is_none() && self.created_after.is_none() && self.name.is_none()
+    }
+
+    fn matches(&self, member: &Member) -> bool {
+        if let Some(joined_after) = self.joined_after {
+            if member
+                .joined_at
+                .is_none_or(|joined_at| joined_at.unix_timestamp() < joined_after)
+            {
+                return false;
+            }
+        }
+
+        if let Some(created_after) = self.created_after {
+            if member.user.id.created_at().unix_timestamp() < created_after {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.name {
+            let names = [
+                Some(member.user.name.as_str()),
+                member.user.global_name.as_deref(),
+                member.nick.as_deref(),
+            ];
+
+            if !names
+                .into_iter()
+                .flatten()
+                .any(|name| matches_pattern(pattern, &name.to_lowercase()))
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Matches `text` against a pattern where `*` stands for any run of characters and `?` for any
+/// single character.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was and the text position it's currently standing in for
+    let mut backtrack = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            backtrack = Some((star, matched + 1));
+            p = star + 1;
+            t = matched + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Every member of a guild, keyed by user ID.
+async fn guild_members(
+    ctx: &Context,
+    guild_id: GuildId,
+) -> Result<HashMap<UserId, Member>, CommandExecutionError> {
+    let mut members = HashMap::new();
+    let mut after = None;
+
+    loop {
+        let page = guild_id
+            .members(&ctx.http, Some(1000), after)
+            .await
+            .map_err(|e| {
+                Report::from(e)
+                    .change_context(CommandExecutionError)
+                    .attach_printable("Failed to list guild members")
+            })?;
+
+        let Some(last) = page.last() else {
+            break;
+        };
+
+        after = Some(last.user.id);
+        let full = page.len() == 1000;
+
+        members.extend(page.into_iter().map(|member| (member.user.id, member)));
+
+        if !full {
+            break;
+        }
+    }
+
+    Ok(members)
+}
+
+/// Drops users the moderator may not ban, returning the rest and why the others were skipped.
+fn guard_targets(
+    guild: &PartialGuild,
+    moderator: &Member,
+    members: &HashMap<UserId, Member>,
+    candidates: Vec<UserId>,
+) -> (Vec<UserId>, Vec<String>) {
+    let mut targets = vec![];
+    let mut skipped = vec![];
+
+    for candidate in candidates {
+        let member = members.get(&candidate);
+
+        if member.is_some_and(|member| member.user.bot) {
+            skipped.push(format!("<@{}>: bots are never mass banned", candidate));
+        } else if let Some(refusal) = refusal(guild, moderator, candidate, member, "ban") {
+            skipped.push(format!("<@{}>: {}", candidate, refusal));
+        } else {
+            targets.push(candidate);
+        }
+    }
+
+    (targets, skipped)
+}
+
+fn preview_line(target: UserId, member: Option<&Member>) -> String {
+    match member {
+        Some(member) => format!(
+            "<@{}> `{}` joined {}, created <t:{}:R>",
+            target,
+            member.user.name,
+            member
+                .joined_at
+                .map(|joined_at| format!("<t:{}:R>", joined_at.unix_timestamp()))
+                .unwrap_or_else(|| String::from("at an unknown time")),
+            target.created_at().unix_timestamp()
+        ),
+        None => format!(
+            "<@{}> not in the server, created <t:{}:R>",
+            target,
+            target.created_at().unix_timestamp()
+        ),
+    }
+}
+
+fn list_problems(content: &mut String, problems: &[String]) {
+    for problem in problems.iter().take(REPORTED_PROBLEMS) {
+        content.push('\n');
+        content.push_str(problem);
+    }
+
+    if problems.len() > REPORTED_PROBLEMS {
+        content.push_str(&format!(
+            "\n...and {} more",
+            problems.len() - REPORTED_PROBLEMS
+        ));
+    }
+}
+
+fn preview(
+    pending: &PendingMassban,
+    members: &HashMap<UserId, Member>,
+    skipped: &[String],
+) -> CreateEmbed {
+    let mut description = pending
+        .targets
+        .iter()
+        .take(PREVIEWED_TARGETS)
+        .map(|target| preview_line(*target, members.get(target)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if pending.targets.len() > PREVIEWED_TARGETS {
+        description.push_str(&format!(
+            "\n...and {} more",
+            pending.targets.len() - PREVIEWED_TARGETS
+        ));
+    }
+
+    if !skipped.is_empty() {
+        description.push_str(&format!("\n\n**Skipped {}**", skipped.len()));
+        list_problems(&mut description, skipped);
+    }
+
+    CreateEmbed::new()
+        .title(format!("Mass ban {} users?", pending.targets.len()))
+        .description(description)
+        .field(
+            "Reason",
+            pending.reason.as_deref().unwrap_or("No reason given"),
+            false,
+        )
+}
+
+/// Confirm and cancel buttons for the mass ban stored under `key`.
+pub fn buttons(key: u64, targets: usize, cancel_label: &str) -> Vec<CreateActionRow> {
+    let buttons = vec![
+        CreateButton::new(format!("massban:confirm:{}", key))
+            .label(format!("Ban {} users", targets))
+            .style(ButtonStyle::Danger),
+        CreateButton::new(format!("massban:cancel:{}", key))
+            .label(cancel_label)
+            .style(ButtonStyle::Secondary),
+    ];
+
+    vec![CreateActionRow::Buttons(buttons)]
+}
+
+/// Keeps a mass ban until one of its [`buttons`] is pressed.
+pub fn store(handler: &BotEvents, key: u64, pending: PendingMassban) {
+    let mut massbans = handler.massbans.lock().unwrap();
+    massbans.retain(|_, pending| !pending.expired());
+    massbans.insert(key, pending);
+}
+
+/// Bans a confirmed mass ban's targets in batches, editing the message with progress as it goes.
+async fn run(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    mut pending: PendingMassban,
+) -> Result<(), CommandExecutionError> {
+    let mut skipped = vec![];
+
+    // Raid alerts were never checked against a moderator, so check against whoever confirmed
+    let moderator_id = match pending.moderator_id {
+        Some(moderator_id) => moderator_id,
+        None => {
+            let Some(moderator) = &interaction.member else {
+                return Err(Report::from(CommandExecutionError)
+                    .attach_printable("Raid alert confirmed outside a guild"));
+            };
+
+            let guild = pending
+                .guild_id
+                .to_partial_guild(&ctx.http)
+                .await
+                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+            let members = guild_members(ctx, guild.id).await?;
+
+            let (targets, refused) = guard_targets(&guild, moderator, &members, pending.targets);
+            pending.targets = targets;
+            skipped = refused;
+
+            moderator.user.id
+        }
+    };
+
+    let delete_messages = delete_window(handler, pending.guild_id, pending.delete_messages);
+    let options = BanOptions {
+        reason: pending.reason.as_deref(),
+        delete_messages: Some(delete_messages),
+        ..Default::default()
+    };
+
+    let mut banned = 0;
+    let mut processed = 0;
+    let mut failed = vec![];
+
+    for (batch, targets) in pending.targets.chunks(BATCH_SIZE).enumerate() {
+        if batch > 0 {
+            time::sleep(BATCH_PAUSE).await;
+        }
+
+        for target in targets {
+            match apply_ban(
+                handler,
+                ctx,
+                pending.guild_id,
+                moderator_id,
+                *target,
+                &options,
+                options.reason,
+            )
+            .await
+            {
+                Ok(applied) => {
+                    banned += 1;
+
+                    if let Some(problem) = applied.schedule_problem {
+                        failed.push(format!("<@{}>: {}", target, problem));
+                    }
+                }
+                Err(err) => {
+                    warn!("Failed to mass ban {}: {:?}", target, err);
+                    failed.push(format!("<@{}>: failed to ban", target));
+                }
+            }
+        }
+
+        // Counted separately, as bans with a schedule problem are both banned and listed in failed
+        processed += targets.len();
+
+        let progress = EditInteractionResponse::new().content(format!(
+            "Banning... {} of {} done",
+            processed,
+            pending.targets.len()
+        ));
+
+        if let Err(err) = interaction.edit_response(&ctx.http, progress).await {
+            warn!("Failed to update mass ban progress: {:?}", err);
+        }
+    }
+
+    let mut content = format!("Banned {} of {} users", banned, pending.targets.len());
+
+    if pending.moderator_id.is_none() {
+        content.push_str(&format!(", confirmed by <@{}>", moderator_id));
+    }
+
+    if banned > 0 {
+        content.push_str(describe_deletion(delete_messages));
+    }
+
+    failed.extend(skipped);
+    list_problems(&mut content, &failed);
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await
+        .map(|_| ())
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+}
+
+#[derive(Debug)]
+pub struct MassbanCommand;
+
+impl CommandInfo for MassbanCommand {
+    fn name(&self) -> String {
+        String::from("massban")
+    }
+
+    fn description(&self) -> String {
+        String::from("Ban every member matching some filters, or a list of user IDs")
+    }
+}
+
+#[async_trait]
+impl Command for MassbanCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut joined_arg = None;
+        let mut age_arg = None;
+        let mut filter = MemberFilter::default();
+        let mut file_arg = None;
+        let mut reason = None;
+        let mut delete_arg = None;
+        let mut delete_messages = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("joined_within", CommandDataOptionValue::String(window)) => {
+                    joined_arg = Some(window.as_str())
+                }
+                ("account_age", CommandDataOptionValue::String(age)) => {
+                    age_arg = Some(age.as_str())
+                }
+                ("name", CommandDataOptionValue::String(pattern)) => {
+                    filter.name = Some(pattern.to_lowercase())
+                }
+                ("file", CommandDataOptionValue::Attachment(attachment_id)) => {
+                    file_arg = interaction.data.resolved.attachments.get(attachment_id)
+                }
+                ("reason", CommandDataOptionValue::String(ban_reason)) => {
+                    reason = Some(ban_reason.clone())
+                }
+                ("delete_messages", CommandDataOptionValue::String(window)) => {
+                    delete_arg = Some(window.as_str())
+                }
+                _ => {}
+            }
+        }
+
+        for (input, cutoff) in [
+            (joined_arg, &mut filter.joined_after),
+            (age_arg, &mut filter.created_after),
+        ] {
+            let Some(input) = input else {
+                continue;
+            };
+
+            let Some(window) = duration::parse(input) else {
+                return reply_ephemeral(
+                    ctx,
+                    interaction,
+                    format!(
+                        "`{}` isn't a valid duration. Use something like `30m`, `12h` or `7d`.",
+                        input
+                    ),
+                )
+                .await;
+            };
+
+            *cutoff = Some(unix_now().saturating_sub(window.as_secs()) as i64);
+        }
+
+        if let Some(input) = delete_arg {
+            match DeleteMessages::parse(input) {
+                Some(window) => delete_messages = Some(window),
+                None => {
+                    return reply_ephemeral(
+                        ctx,
+                        interaction,
+                        format!(
+                            "`{}` isn't a valid message deletion window. Use `none`, `1h`, `24h` or `7d`.",
+                            input
+                        ),
+                    )
+                    .await;
+                }
+            }
+        }
+
+        if filter.is_empty() == file_arg.is_none() {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                "Give either filters (`joined_within`, `account_age`, `name`) or a `file` of user IDs",
+            )
+            .await;
+        }
+
+        let mut invalid = vec![];
+        let listed = match file_arg {
+            Some(file) => {
+                let Some(contents) = download_id_list(file).await? else {
+                    return reply_ephemeral(ctx, interaction, id_list_too_large(file)).await;
+                };
+
+                let (ids, not_ids) = parse_user_ids(&contents);
+                invalid = not_ids;
+
+                Some(ids)
+            }
+            None => None,
+        };
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+        let members = guild_members(ctx, guild.id).await?;
+
+        let candidates = listed.unwrap_or_else(|| {
+            let mut matched = members
+                .values()
+                .filter(|member| filter.matches(member))
+                .map(|member| member.user.id)
+                .collect::<Vec<_>>();
+            matched.sort();
+
+            matched
+        });
+
+        let (targets, mut skipped) =
+            guard_targets(&guild, interaction_member, &members, candidates);
+
+        if !invalid.is_empty() {
+            skipped.push(format!("{} entries that aren't user IDs", invalid.len()));
+        }
+
+        if targets.is_empty() {
+            let mut content = String::from("No users matched");
+            list_problems(&mut content, &skipped);
+
+            return edit_reply(ctx, interaction, content).await;
+        }
+
+        if targets.len() > MAX_MASSBAN_TARGETS {
+            return edit_reply(
+                ctx,
+                interaction,
+                format!(
+                    "That's {} users, at most {} can be mass banned at once. Narrow the filters down.",
+                    targets.len(),
+                    MAX_MASSBAN_TARGETS
+                ),
+            )
+            .await;
+        }
+
+        let pending = PendingMassban::new(
+            guild.id,
+            Some(interaction.user.id),
+            targets,
+            reason,
+            delete_messages,
+        );
+
+        let key = interaction.id.get();
+        let embed = preview(&pending, &members, &skipped);
+        let components = buttons(key, pending.targets.len(), "Cancel");
+        store(handler, key, pending);
+
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .embed(embed)
+                    .components(components),
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "joined_within",
+                "Members who joined within this long, e.g. 30m or 2h",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "account_age",
+                "Accounts created within this long, e.g. 1d or 7d",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "name",
+                "Name pattern, * matches anything and ? any one character, e.g. free*nitro",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Attachment,
+                "file",
+                "A text file of user IDs to ban instead of filtering members",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "reason",
+                "Reason for the bans",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "delete_messages",
+                    "How much of their recent message history to delete",
+                )
+                .add_string_choice("Don't delete any", "none")
+                .add_string_choice("Previous hour", "1h")
+                .add_string_choice("Previous 24 hours", "24h")
+                .add_string_choice("Previous 7 days", "7d"),
+            )
+            .default_member_permissions(Permissions::BAN_MEMBERS)
+            .dm_permission(false)
+    }
+
+    async fn component(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &mut ComponentInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut parts = interaction.data.custom_id.split(':').skip(1);
+        let (Some(action), Some(key)) = (
+            parts.next(),
+            parts.next().and_then(|key| key.parse::<u64>().ok()),
+        ) else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable(format!("Unknown component {}", interaction.data.custom_id)));
+        };
+
+        let pending = {
+            let mut massbans = handler.massbans.lock().unwrap();
+
+            match massbans.get(&key) {
+                Some(pending) => match pending.refusal(interaction) {
+                    Some(refusal) => Err(refusal),
+                    None if pending.expired() => {
+                        massbans.remove(&key);
+                        Err("This mass ban has expired")
+                    }
+                    None => Ok(massbans.remove(&key)),
+                },
+                None => Ok(None),
+            }
+        };
+
+        let pending = match pending {
+            Ok(pending) => pending,
+            Err(refusal) => {
+                let message = CreateInteractionResponseMessage::new()
+                    .content(refusal)
+                    .ephemeral(true);
+
+                return interaction
+                    .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+                    .await
+                    .map_err(|e| Report::from(e).change_context(CommandExecutionError));
+            }
+        };
+
+        let content = match (action, &pending) {
+            (_, None) => String::from("This mass ban has already been handled"),
+            ("cancel", Some(pending)) if pending.moderator_id.is_none() => {
+                format!("Dismissed by <@{}>", interaction.user.id)
+            }
+            ("cancel", Some(_)) => String::from("Mass ban cancelled"),
+            ("confirm", Some(pending)) => {
+                format!("Banning... 0 of {} done", pending.targets.len())
+            }
+            _ => {
+                return Err(Report::from(CommandExecutionError)
+                    .attach_printable(format!("Unknown component {}", interaction.data.custom_id)))
+            }
+        };
+
+        let message = CreateInteractionResponseMessage::new()
+            .content(content)
+            .components(vec![]);
+
+        interaction
+            .create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(message))
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        match (action, pending) {
+            ("confirm", Some(pending)) => run(handler, ctx, interaction, pending).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcard_patterns() {
+        assert!(matches_pattern("free*nitro", "free_discord_nitro"));
+        assert!(matches_pattern("free*nitro", "freenitro"));
+        assert!(matches_pattern("raider??", "raider42"));
+        assert!(matches_pattern("*bot*", "spam-bot-7"));
+        assert!(matches_pattern("*", ""));
+
+        assert!(!matches_pattern("raider??", "raider4"));
+        assert!(!matches_pattern("free*nitro", "free nitro!"));
+        assert!(!matches_pattern("exact", "exactly"));
+    }
+}
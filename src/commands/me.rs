@@ -0,0 +1,202 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::all::{
+    ButtonStyle, CommandInteraction, ComponentInteraction, Context, CreateActionRow,
+    CreateAttachment, CreateButton, CreateCommand, CreateEmbed, EditInteractionResponse,
+};
+use tracing::error;
+
+use crate::{
+    event_handler::BotEvents,
+    models::{Addon, User},
+};
+
+use super::{Command, CommandExecutionError, CommandInfo};
+
+/// Discord caps embed field values at 1024 characters.
+const FIELD_LIMIT: usize = 1024;
+
+#[derive(Debug)]
+pub struct MeCommand;
+
+impl CommandInfo for MeCommand {
+    fn name(&self) -> String {
+        String::from("me")
+    }
+
+    fn description(&self) -> String {
+        String::from("Shows your Pulsar Link account and owned addons")
+    }
+
+    fn global(&self) -> bool {
+        true
+    }
+}
+
+fn describe_account(user: &User, addons: Option<&[Addon]>) -> CreateEmbed {
+    let or_missing = |value: Option<String>| value.unwrap_or_else(|| String::from("Not linked"));
+
+    let addons = match addons {
+        Some([]) => String::from("You don't own any addons on this account."),
+        Some(addons) => {
+            let mut list = String::new();
+
+            for addon in addons.iter().filter(|addon| addon.error.is_none()) {
+                let line = format!(
+                    "[{}](<https://www.gmodstore.com/market/view/{}>)\n",
+                    addon.name.as_deref().unwrap_or(""),
+                    addon.id.as_deref().unwrap_or("")
+                );
+
+                if list.len() + line.len() > FIELD_LIMIT {
+                    break;
+                }
+
+                list.push_str(&line);
+            }
+
+            list
+        }
+        None => String::from("Unable to load your addons right now."),
+    };
+
+    CreateEmbed::new()
+        .title("Your Pulsar Link account")
+        .field("Pulsar ID", or_missing(user.id.clone()), false)
+        .field(
+            "Steam ID",
+            or_missing(user.steam_id.map(|id| id.to_string())),
+            true,
+        )
+        .field("Gmodstore ID", or_missing(user.gmodstore_id.clone()), true)
+        .field(
+            "Discord ID",
+            or_missing(user.discord_id.map(|id| id.to_string())),
+            true,
+        )
+        .field("Owned addons", addons, false)
+}
+
+#[async_trait]
+impl Command for MeCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let response = match handler.api.user_by_discord(interaction.user.id).await {
+            Ok(Some(user)) => {
+                let addons = match handler
+                    .api
+                    .user_addons(user.id.as_deref().unwrap_or_default())
+                    .await
+                {
+                    Ok(addons) => Some(addons),
+                    Err(err) => {
+                        error!("Failed to get addons for {}: {:?}", interaction.user.id, err);
+                        None
+                    }
+                };
+
+                // Unlinking waits on a backend endpoint that only removes the Discord identity,
+                // deleting the user would take their Steam and Gmodstore links with it.
+                let buttons = vec![CreateButton::new("me:export")
+                    .label("Request data export")
+                    .style(ButtonStyle::Secondary)];
+
+                EditInteractionResponse::new()
+                    .embed(describe_account(&user, addons.as_deref()))
+                    .components(vec![CreateActionRow::Buttons(buttons)])
+            }
+            Ok(None) => EditInteractionResponse::new().content(
+                "Your Discord account isn't linked to Pulsar Link yet. Use `/verify steps` to get started.",
+            ),
+            Err(err) => {
+                error!("Failed to look up {}: {:?}", interaction.user.id, err);
+
+                EditInteractionResponse::new()
+                    .content("Unable to load your account. Please try again later.")
+            }
+        };
+
+        interaction
+            .edit_response(&ctx.http, response)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        Ok(())
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .dm_permission(true)
+    }
+
+    async fn component(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &mut ComponentInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        match interaction.data.custom_id.as_str() {
+            "me:export" => export(handler, ctx, interaction).await,
+            _ => Err(Report::from(CommandExecutionError)
+                .attach_printable(format!("Unknown component {}", interaction.data.custom_id))),
+        }
+    }
+}
+
+/// Sends the user everything the backend holds about them as a JSON file.
+async fn export(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &mut ComponentInteraction,
+) -> Result<(), CommandExecutionError> {
+    interaction
+        .defer_ephemeral(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    // Always resolve the account from the clicking user, never from the message.
+    let export = match handler.api.user_by_discord(interaction.user.id).await {
+        Ok(Some(User {
+            id: Some(pulsar_id),
+            ..
+        })) => handler.api.user_export(&pulsar_id).await.map(Some),
+        Ok(_) => Ok(None),
+        Err(err) => Err(err),
+    };
+
+    let response = match export {
+        Ok(Some(export)) => {
+            let json = serde_json::to_vec_pretty(&export)
+                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+            EditInteractionResponse::new()
+                .content("Here's everything Pulsar Link holds about you.")
+                .new_attachment(CreateAttachment::bytes(json, "pulsar-link-export.json"))
+        }
+        Ok(None) => EditInteractionResponse::new()
+            .content("Your Discord account isn't linked to Pulsar Link."),
+        Err(err) => {
+            error!("Failed to export {}: {:?}", interaction.user.id, err);
+
+            EditInteractionResponse::new()
+                .content("Unable to export your data. Please try again later.")
+        }
+    };
+
+    interaction
+        .edit_response(&ctx.http, response)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    Ok(())
+}
@@ -3,17 +3,33 @@ use std::fmt;
 use async_trait::async_trait;
 use error_stack::Context;
 use serenity::{
-    builder::CreateCommand, client::Context as SerenityContext,
-    model::application::CommandInteraction,
+    builder::CreateCommand,
+    client::Context as SerenityContext,
+    model::application::{CommandInteraction, CommandType, ComponentInteraction, ModalInteraction},
 };
 
 use crate::event_handler::BotEvents;
 
 mod ban;
+mod cases;
+mod context_menu;
+mod kick;
+mod lockdown;
 mod user_create;
 mod user_get;
 mod addons;
-mod verify;
+mod appeals;
+pub mod massban;
+mod me;
+mod moderation;
+mod purge;
+mod response;
+mod slowmode;
+mod sync_roles;
+pub mod timeout;
+mod unban;
+mod warn;
+pub mod verify;
 
 #[async_trait]
 pub trait Command
@@ -31,11 +47,51 @@ where
         &self,
         handler: &BotEvents,
     ) -> CreateCommand;
+
+    /// Handles a button or select menu whose custom ID is prefixed with `<name>:`.
+    async fn component(
+        &self,
+        _handler: &BotEvents,
+        _ctx: &SerenityContext,
+        _interaction: &mut ComponentInteraction,
+    ) -> error_stack::Result<(), CommandExecutionError> {
+        Ok(())
+    }
+
+    /// Handles a submitted modal whose custom ID is prefixed with `<name>:`.
+    async fn modal(
+        &self,
+        _handler: &BotEvents,
+        _ctx: &SerenityContext,
+        _interaction: &ModalInteraction,
+    ) -> error_stack::Result<(), CommandExecutionError> {
+        Ok(())
+    }
+
+    /// Suggests values for options registered with autocomplete enabled.
+    async fn autocomplete(
+        &self,
+        _handler: &BotEvents,
+        _ctx: &SerenityContext,
+        _interaction: &CommandInteraction,
+    ) -> error_stack::Result<(), CommandExecutionError> {
+        Ok(())
+    }
 }
 
 pub trait CommandInfo {
     fn name(&self) -> String;
     fn description(&self) -> String;
+
+    /// Context menu commands return [`CommandType::User`] or [`CommandType::Message`].
+    fn kind(&self) -> CommandType {
+        CommandType::ChatInput
+    }
+
+    /// Global commands are registered once for every guild and DMs, rather than per guild.
+    fn global(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug)]
@@ -52,9 +108,28 @@ impl Context for CommandExecutionError {}
 pub fn load_commands() -> Vec<Box<dyn Command + Send + Sync>> {
     vec![
         Box::new(ban::BanCommand),
+        Box::new(unban::UnbanCommand),
+        Box::new(massban::MassbanCommand),
+        Box::new(kick::KickCommand),
+        Box::new(timeout::TimeoutCommand),
+        Box::new(warn::WarnCommand),
+        Box::new(warn::WarningsCommand),
+        Box::new(warn::UnwarnCommand),
+        Box::new(cases::CasesCommand),
+        Box::new(cases::CaseCommand),
+        Box::new(purge::PurgeCommand),
+        Box::new(lockdown::LockdownCommand),
+        Box::new(lockdown::UnlockCommand),
+        Box::new(slowmode::SlowmodeCommand),
         Box::new(user_create::UserCreateCommand),
         Box::new(user_get::UserGetCommand),
         Box::new(addons::UserAddonsCommand),
         Box::new(verify::VerifyCommand),
+        Box::new(sync_roles::SyncRolesCommand),
+        Box::new(me::MeCommand),
+        Box::new(context_menu::LookupUserContextCommand),
+        Box::new(context_menu::ViewAddonsContextCommand),
+        Box::new(context_menu::BanContextCommand),
+        Box::new(context_menu::LookupAuthorContextCommand),
     ]
 }
@@ -0,0 +1,533 @@
+use error_stack::{Report, Result};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serenity::{
+    builder::{
+        CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+        EditInteractionResponse,
+    },
+    client::Context,
+    http::{Http, HttpError, LightMethod, Request, Route},
+    model::{
+        application::CommandInteraction,
+        channel::{Attachment, GuildChannel},
+        guild::{Member, PartialGuild},
+        id::{ChannelId, GuildId, UserId},
+        Permissions,
+    },
+};
+
+use tracing::warn;
+
+use crate::{
+    database::cases::{Case, NewCase},
+    event_handler::BotEvents,
+    mod_log,
+};
+
+use super::CommandExecutionError;
+
+/// Largest ID list file accepted, in bytes.
+const MAX_ID_FILE_SIZE: u32 = 256 * 1024;
+
+/// JSON error code Discord returns for users who aren't in the guild.
+const UNKNOWN_MEMBER: isize = 10007;
+
+fn highest_role_position(guild: &PartialGuild, member: &Member) -> Option<u16> {
+    member
+        .roles
+        .iter()
+        .filter_map(|role| guild.roles.get(role))
+        .map(|role| role.position)
+        .max()
+}
+
+/// The guild and invoking member of a moderation command.
+pub async fn moderation_context<'a>(
+    ctx: &Context,
+    interaction: &'a CommandInteraction,
+) -> Result<(PartialGuild, &'a Member), CommandExecutionError> {
+    let Some(member) = &interaction.member else {
+        return Err(Report::from(CommandExecutionError)
+            .attach_printable("Failed to fetch member from interaction"));
+    };
+
+    let Some(guild_id) = interaction.guild_id else {
+        return Err(Report::from(CommandExecutionError)
+            .attach_printable("Moderation command used outside a guild"));
+    };
+
+    let guild = guild_id
+        .to_partial_guild(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    Ok((guild, member))
+}
+
+/// The user's membership of a guild, or `None` only when Discord says they aren't a member. Any
+/// other failure is returned, so an outage or rate limit can't skip the hierarchy checks.
+pub async fn find_member(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: UserId,
+) -> Result<Option<Member>, CommandExecutionError> {
+    match guild_id.member(ctx, user).await {
+        Ok(member) => Ok(Some(member)),
+        Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(response)))
+            if response.error.code == UNKNOWN_MEMBER =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(Report::from(err)
+            .change_context(CommandExecutionError)
+            .attach_printable(format!("Failed to fetch member {}", user))),
+    }
+}
+
+async fn guild_channel(
+    ctx: &Context,
+    channel_id: ChannelId,
+) -> Result<Option<GuildChannel>, CommandExecutionError> {
+    channel_id
+        .to_channel(&ctx.http)
+        .await
+        .map(|channel| channel.guild())
+        .map_err(|e| {
+            Report::from(e)
+                .change_context(CommandExecutionError)
+                .attach_printable(format!("Failed to fetch channel {}", channel_id))
+        })
+}
+
+/// The member's permissions in a channel, with its overwrites, or a thread's parent's, applied.
+/// `default_member_permissions` is only checked in the channel a command is used in, so commands
+/// acting on another channel have to check this themselves. Channels from other guilds grant
+/// nothing.
+pub async fn channel_permissions(
+    ctx: &Context,
+    guild: &PartialGuild,
+    member: &Member,
+    channel_id: ChannelId,
+) -> Result<Permissions, CommandExecutionError> {
+    let mut channel = guild_channel(ctx, channel_id).await?;
+
+    if let Some(parent_id) = channel
+        .as_ref()
+        .filter(|channel| channel.thread_metadata.is_some())
+        .and_then(|thread| thread.parent_id)
+    {
+        channel = guild_channel(ctx, parent_id).await?;
+    }
+
+    Ok(channel
+        .filter(|channel| channel.guild_id == guild.id)
+        .map(|channel| guild.user_permissions_in(&channel, member))
+        .unwrap_or_else(Permissions::empty))
+}
+
+/// Why `moderator` may not `action` the target, if they may not. The invoker can't act on
+/// themselves or the server owner, and only the owner can act on someone at or above their own
+/// top role. Users who aren't in the guild have no roles, so only the first two checks apply.
+pub fn refusal(
+    guild: &PartialGuild,
+    moderator: &Member,
+    target: UserId,
+    target_member: Option<&Member>,
+    action: &str,
+) -> Option<String> {
+    if target == moderator.user.id {
+        return Some(format!("You're unable to {} yourself...", action));
+    }
+
+    if target == guild.owner_id {
+        return Some(format!("Unable to {} server owner", action));
+    }
+
+    if guild.owner_id == moderator.user.id {
+        return None;
+    }
+
+    let target_role_pos = highest_role_position(guild, target_member?)?;
+    let member_role_pos = highest_role_position(guild, moderator)?;
+
+    if target_role_pos >= member_role_pos {
+        return Some(format!(
+            "You lack sufficient privileges to {} this user",
+            action
+        ));
+    }
+
+    None
+}
+
+/// Bans a user, deleting their messages from the last `delete_message_seconds`. Serenity only
+/// takes whole days, which rules out deleting just the last hour of a raid.
+pub async fn ban(
+    http: &Http,
+    guild_id: GuildId,
+    user_id: UserId,
+    delete_message_seconds: u32,
+    reason: Option<&str>,
+) -> serenity::Result<()> {
+    let headers = reason.and_then(|reason| {
+        let value =
+            HeaderValue::from_str(&utf8_percent_encode(reason, NON_ALPHANUMERIC).to_string())
+                .ok()?;
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Audit-Log-Reason", value);
+
+        Some(headers)
+    });
+
+    let request = Request::new(Route::GuildBan { guild_id, user_id }, LightMethod::Put)
+        .headers(headers)
+        .params(Some(vec![(
+            "delete_message_seconds",
+            delete_message_seconds.to_string(),
+        )]));
+
+    http.request(request).await?;
+
+    Ok(())
+}
+
+/// Whether a ban or unban applies to every configured guild, defaulting to the `ban-sync` setting.
+pub fn syncs_bans(handler: &BotEvents, requested: Option<bool>) -> bool {
+    requested.unwrap_or(handler.cfg.ban_sync)
+}
+
+/// The other configured guilds a ban or unban in `origin` is synced to.
+pub fn sync_guilds(handler: &BotEvents, origin: GuildId) -> Vec<GuildId> {
+    handler
+        .cfg
+        .guilds
+        .iter()
+        .map(|guild| GuildId::new(*guild as u64))
+        .filter(|guild| *guild != origin)
+        .filter(|guild| {
+            !handler
+                .cfg
+                .guild(guild.get())
+                .is_some_and(|settings| settings.ban_sync_opt_out)
+        })
+        .collect()
+}
+
+/// A guild a ban or unban is synced to, along with the moderator's membership of it.
+pub struct SyncGuild {
+    pub guild: PartialGuild,
+    pub moderator: Member,
+}
+
+impl SyncGuild {
+    /// Why the moderator may not `action` the target in this guild, if they may not. The usual
+    /// hierarchy checks apply against the target's membership here, and failing to look it up
+    /// refuses rather than skipping them.
+    pub async fn refusal(&self, ctx: &Context, target: UserId, action: &str) -> Option<String> {
+        match find_member(ctx, self.guild.id, target).await {
+            Ok(target_member) => refusal(
+                &self.guild,
+                &self.moderator,
+                target,
+                target_member.as_ref(),
+                action,
+            ),
+            Err(err) => {
+                warn!(
+                    "Failed to check {} in synced guild {}: {:?}",
+                    target, self.guild.id, err
+                );
+                Some(String::from("couldn't check their roles there"))
+            }
+        }
+    }
+}
+
+/// The synced guild, or why it's skipped. Moderating one guild grants nothing in another, so the
+/// moderator has to be a member there with Ban Members.
+pub async fn sync_guild(
+    ctx: &Context,
+    guild_id: GuildId,
+    moderator_id: UserId,
+) -> std::result::Result<SyncGuild, &'static str> {
+    let guild = guild_id.to_partial_guild(&ctx.http).await.map_err(|err| {
+        warn!("Failed to fetch synced guild {}: {:?}", guild_id, err);
+        "skipped, couldn't fetch the server"
+    })?;
+
+    let moderator = match find_member(ctx, guild_id, moderator_id).await {
+        Ok(Some(moderator)) => moderator,
+        Ok(None) => return Err("skipped, you aren't a member there"),
+        Err(err) => {
+            warn!(
+                "Failed to fetch moderator {} in synced guild {}: {:?}",
+                moderator_id, guild_id, err
+            );
+            return Err("skipped, couldn't check your permissions there");
+        }
+    };
+
+    if !guild.member_permissions(&moderator).ban_members() {
+        return Err("skipped, you don't have Ban Members there");
+    }
+
+    Ok(SyncGuild { guild, moderator })
+}
+
+/// Reason recorded for an action synced from another guild.
+pub fn synced_reason(origin: &PartialGuild, reason: Option<&str>) -> String {
+    format!(
+        "Synced from {}: {}",
+        origin.name,
+        reason.unwrap_or("No reason given")
+    )
+}
+
+pub fn guild_name(ctx: &Context, guild_id: GuildId) -> String {
+    guild_id
+        .name(&ctx.cache)
+        .unwrap_or_else(|| guild_id.to_string())
+}
+
+/// Whether to DM the user about an action, defaulting to the guild's `ban-notify` setting.
+pub fn should_notify(handler: &BotEvents, guild_id: GuildId, requested: Option<bool>) -> bool {
+    requested.unwrap_or_else(|| {
+        handler
+            .cfg
+            .guild(guild_id.get())
+            .is_some_and(|settings| settings.ban_notify.enabled)
+    })
+}
+
+/// The start of the DM telling a user about an action taken against them.
+pub fn notice(title: String, reason: Option<&str>) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .field("Reason", reason.unwrap_or("No reason given"), false)
+}
+
+/// DMs a user about an action taken against them, returning whether it was delivered.
+pub async fn notify(ctx: &Context, user: UserId, notice: CreateEmbed) -> bool {
+    send_notice(ctx, user, CreateMessage::new().embed(notice)).await
+}
+
+/// Like [`notify`], for notices that need more than an embed.
+pub async fn send_notice(ctx: &Context, user: UserId, notice: CreateMessage) -> bool {
+    match user.direct_message(ctx, notice).await {
+        Ok(_) => true,
+        Err(err) => {
+            warn!("Failed to send notice to {}: {:?}", user, err);
+            false
+        }
+    }
+}
+
+/// Describes an `action` that failed after the user may already have been told about it. If they
+/// were, they're sent a DM saying to disregard the notice.
+pub async fn retract_notice(
+    ctx: &Context,
+    user: UserId,
+    guild_name: &str,
+    notified: Option<bool>,
+    action: &str,
+) -> String {
+    if notified != Some(true) {
+        return format!("failed to {}", action);
+    }
+
+    let correction = CreateEmbed::new()
+        .title(format!(
+            "Your {} from {} didn't go through",
+            action, guild_name
+        ))
+        .description(format!(
+            "Please disregard the {} notice you were just sent.",
+            action
+        ));
+
+    if notify(ctx, user, correction).await {
+        format!(
+            "failed to {}, they were sent a DM saying to disregard the {} notice",
+            action, action
+        )
+    } else {
+        format!(
+            "failed to {} after they were sent the {} notice, and they couldn't be told it didn't go through",
+            action, action
+        )
+    }
+}
+
+/// Reply suffix saying whether the user was DMed about the `action`, if a DM was attempted.
+pub fn describe_notice(notified: Option<bool>, action: &str) -> String {
+    match notified {
+        Some(true) => format!("\nThey were sent a DM about the {}", action),
+        Some(false) => format!(
+            "\nCouldn't DM them about the {}, their DMs are likely closed",
+            action
+        ),
+        None => String::new(),
+    }
+}
+
+/// Opens a case for an action taken in a guild and posts it to the guild's mod log.
+pub async fn record_case(handler: &BotEvents, ctx: &Context, case: NewCase) -> Option<Case> {
+    let mod_log_channel = handler
+        .cfg
+        .guild(case.guild_id)
+        .and_then(|settings| settings.mod_log_channel());
+
+    mod_log::record(&ctx.http, &handler.db, mod_log_channel, case).await
+}
+
+/// Posts a case opened ahead of its action to the guild's mod log, once the action went through.
+pub async fn post_case(handler: &BotEvents, ctx: &Context, case: &Case) {
+    let mod_log_channel = handler
+        .cfg
+        .guild(case.guild_id)
+        .and_then(|settings| settings.mod_log_channel());
+
+    mod_log::post(&ctx.http, mod_log_channel, case).await
+}
+
+/// Suffix mentioning the case an action was recorded as, if it was.
+pub fn describe_case(case: Option<&Case>) -> String {
+    case.map(|case| format!(" (case #{})", case.id))
+        .unwrap_or_default()
+}
+
+/// Cuts text down to at most `max` characters, marking where it was cut.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Replaces the response to a deferred interaction.
+pub async fn edit_reply(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    content: impl Into<String>,
+) -> Result<(), CommandExecutionError> {
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            Report::from(e)
+                .change_context(CommandExecutionError)
+                .attach_printable("Failed to reply to message")
+        })
+}
+
+pub async fn reply_ephemeral(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    content: impl Into<String>,
+) -> Result<(), CommandExecutionError> {
+    let message = CreateInteractionResponseMessage::new()
+        .content(content)
+        .ephemeral(true);
+
+    interaction
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+        .await
+        .map_err(|e| {
+            Report::from(e)
+                .change_context(CommandExecutionError)
+                .attach_printable("Failed to reply to message")
+        })
+}
+
+/// Downloads an attached list of user IDs, or returns `None` if it's over [`MAX_ID_FILE_SIZE`].
+pub async fn download_id_list(file: &Attachment) -> Result<Option<String>, CommandExecutionError> {
+    if file.size > MAX_ID_FILE_SIZE {
+        return Ok(None);
+    }
+
+    let contents = file.download().await.map_err(|e| {
+        Report::from(e)
+            .change_context(CommandExecutionError)
+            .attach_printable("Failed to download ID list")
+    })?;
+
+    Ok(Some(String::from_utf8_lossy(&contents).into_owned()))
+}
+
+pub fn id_list_too_large(file: &Attachment) -> String {
+    format!(
+        "`{}` is too large, ID lists can be at most {} KB",
+        file.filename,
+        MAX_ID_FILE_SIZE / 1024
+    )
+}
+
+/// Accepts a bare user ID or a mention.
+pub fn parse_user_id(input: &str) -> Option<UserId> {
+    let id = input
+        .trim()
+        .trim_start_matches("<@")
+        .trim_start_matches('!')
+        .trim_end_matches('>');
+
+    id.parse::<u64>()
+        .ok()
+        .filter(|id| *id != 0)
+        .map(UserId::new)
+}
+
+/// Every user ID in a list separated by whitespace, commas or semicolons, without duplicates,
+/// along with the entries that aren't IDs.
+pub fn parse_user_ids(input: &str) -> (Vec<UserId>, Vec<String>) {
+    let mut ids = vec![];
+    let mut invalid = vec![];
+
+    for entry in input
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|entry| !entry.is_empty())
+    {
+        match parse_user_id(entry) {
+            Some(id) if !ids.contains(&id) => ids.push(id),
+            Some(_) => {}
+            None => invalid.push(entry.to_string()),
+        }
+    }
+
+    (ids, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ids_and_mentions() {
+        let id = Some(UserId::new(123456789012345678));
+
+        assert_eq!(parse_user_id("123456789012345678"), id);
+        assert_eq!(parse_user_id("<@123456789012345678>"), id);
+        assert_eq!(parse_user_id("<@!123456789012345678>"), id);
+        assert_eq!(parse_user_id("someone"), None);
+        assert_eq!(parse_user_id("0"), None);
+    }
+
+    #[test]
+    fn truncates_long_text() {
+        assert_eq!(truncate("short", 5), "short");
+        assert_eq!(truncate("a bit longer", 6), "a bit…");
+        assert_eq!(truncate("ééé", 2), "é…");
+    }
+
+    #[test]
+    fn parses_id_lists() {
+        let (ids, invalid) = parse_user_ids("1, 2\n<@3>;2 nope\r\n");
+
+        assert_eq!(ids, vec![UserId::new(1), UserId::new(2), UserId::new(3)]);
+        assert_eq!(invalid, vec![String::from("nope")]);
+    }
+}
@@ -0,0 +1,404 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{CreateCommand, CreateCommandOption, CreateEmbed, CreateMessage, GetMessages},
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        channel::{ChannelType, Message},
+        id::{ChannelId, MessageId, UserId},
+        Permissions, Timestamp,
+    },
+};
+use tracing::{error, warn};
+
+use crate::{duration::unix_now, event_handler::BotEvents};
+
+use super::{
+    moderation::{channel_permissions, edit_reply, moderation_context, reply_ephemeral},
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Most messages a single purge deletes.
+const MAX_PURGE: u64 = 500;
+
+/// Most messages looked through for matches, so narrow filters don't page through a channel's
+/// entire history.
+const MAX_SCANNED: usize = 2000;
+
+/// Discord only bulk deletes messages younger than two weeks. The margin covers the time spent
+/// collecting them.
+const BULK_DELETE_MAX_AGE: i64 = 14 * 24 * 60 * 60 - 60 * 60;
+
+/// Most older messages deleted one at a time, as each costs a request of its own.
+const MAX_SINGLE_DELETES: usize = 50;
+
+#[derive(Debug)]
+pub struct PurgeCommand;
+
+impl CommandInfo for PurgeCommand {
+    fn name(&self) -> String {
+        String::from("purge")
+    }
+
+    fn description(&self) -> String {
+        String::from("Bulk delete recent messages in a channel")
+    }
+}
+
+/// Which messages `/purge` deletes, every given filter has to match. Pinned messages are always
+/// kept.
+#[derive(Debug, Default)]
+struct PurgeFilter {
+    user: Option<UserId>,
+    links: bool,
+    attachments: bool,
+    bots: bool,
+}
+
+impl PurgeFilter {
+    fn matches(&self, message: &Message) -> bool {
+        !message.pinned
+            && self.user.is_none_or(|user| message.author.id == user)
+            && (!self.links || contains_link(&message.content))
+            && (!self.attachments || !message.attachments.is_empty())
+            && (!self.bots || message.author.bot)
+    }
+
+    fn describe(&self) -> String {
+        let mut filters = vec![];
+
+        if let Some(user) = self.user {
+            filters.push(format!("From <@{}>", user));
+        }
+
+        if self.links {
+            filters.push(String::from("Containing links"));
+        }
+
+        if self.attachments {
+            filters.push(String::from("With attachments"));
+        }
+
+        if self.bots {
+            filters.push(String::from("From bots"));
+        }
+
+        if filters.is_empty() {
+            String::from("None")
+        } else {
+            filters.join("\n")
+        }
+    }
+}
+
+fn contains_link(content: &str) -> bool {
+    let content = content.to_lowercase();
+
+    content.contains("http://") || content.contains("https://") || content.contains("discord.gg/")
+}
+
+/// The newest `count` messages in the channel matching the filter, newest first.
+async fn collect(
+    ctx: &Context,
+    channel: ChannelId,
+    filter: &PurgeFilter,
+    count: usize,
+) -> Result<Vec<Message>, CommandExecutionError> {
+    let mut matched = vec![];
+    let mut scanned = 0;
+    let mut before: Option<MessageId> = None;
+
+    while matched.len() < count && scanned < MAX_SCANNED {
+        let mut request = GetMessages::new().limit(100);
+
+        if let Some(before) = before {
+            request = request.before(before);
+        }
+
+        let page = channel.messages(&ctx.http, request).await.map_err(|e| {
+            Report::from(e)
+                .change_context(CommandExecutionError)
+                .attach_printable("Failed to fetch messages")
+        })?;
+
+        let Some(last) = page.last() else {
+            break;
+        };
+
+        scanned += page.len();
+        before = Some(last.id);
+
+        matched.extend(page.into_iter().filter(|message| filter.matches(message)));
+    }
+
+    matched.truncate(count);
+
+    Ok(matched)
+}
+
+struct Purged {
+    deleted: usize,
+    /// Messages too old to bulk delete that were deleted one at a time.
+    deleted_singly: usize,
+    /// Older messages left alone past [`MAX_SINGLE_DELETES`].
+    skipped: usize,
+    failed: usize,
+}
+
+/// Bulk deletes the messages Discord allows to be, falling back to deleting a limited number of
+/// older ones individually.
+async fn delete(ctx: &Context, channel: ChannelId, messages: Vec<Message>) -> Purged {
+    let cutoff = unix_now() as i64 - BULK_DELETE_MAX_AGE;
+    let (recent, old): (Vec<_>, Vec<_>) = messages
+        .into_iter()
+        .map(|message| message.id)
+        .partition(|id| id.created_at().unix_timestamp() > cutoff);
+
+    let mut purged = Purged {
+        deleted: 0,
+        deleted_singly: 0,
+        skipped: old.len().saturating_sub(MAX_SINGLE_DELETES),
+        failed: 0,
+    };
+
+    for batch in recent.chunks(100) {
+        match channel.delete_messages(&ctx.http, batch).await {
+            Ok(()) => purged.deleted += batch.len(),
+            Err(err) => {
+                warn!("Failed to bulk delete messages in {}: {:?}", channel, err);
+                purged.failed += batch.len();
+            }
+        }
+    }
+
+    for id in old.iter().take(MAX_SINGLE_DELETES) {
+        match channel.delete_message(&ctx.http, id).await {
+            Ok(()) => {
+                purged.deleted += 1;
+                purged.deleted_singly += 1;
+            }
+            Err(err) => {
+                warn!("Failed to delete message {} in {}: {:?}", id, channel, err);
+                purged.failed += 1;
+            }
+        }
+    }
+
+    purged
+}
+
+#[async_trait]
+impl Command for PurgeCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut count = None;
+        let mut channel = interaction.channel_id;
+        let mut filter = PurgeFilter::default();
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("count", CommandDataOptionValue::Integer(value)) => count = Some(*value),
+                ("user", CommandDataOptionValue::User(user_id)) => filter.user = Some(*user_id),
+                ("links", CommandDataOptionValue::Boolean(links)) => filter.links = *links,
+                ("attachments", CommandDataOptionValue::Boolean(attachments)) => {
+                    filter.attachments = *attachments
+                }
+                ("bots", CommandDataOptionValue::Boolean(bots)) => filter.bots = *bots,
+                ("channel", CommandDataOptionValue::Channel(channel_id)) => channel = *channel_id,
+                _ => {}
+            }
+        }
+
+        let Some(count) = count.filter(|count| (1..=MAX_PURGE as i64).contains(count)) else {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!("Give a count between 1 and {}", MAX_PURGE),
+            )
+            .await;
+        };
+
+        let (guild, member) = moderation_context(ctx, interaction).await?;
+        let permissions = channel_permissions(ctx, &guild, member, channel).await?;
+
+        if !permissions.contains(Permissions::VIEW_CHANNEL | Permissions::MANAGE_MESSAGES) {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!(
+                    "You need the View Channel and Manage Messages permissions in <#{}> to purge it",
+                    channel
+                ),
+            )
+            .await;
+        }
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let messages = collect(ctx, channel, &filter, count as usize).await?;
+
+        if messages.is_empty() {
+            return edit_reply(
+                ctx,
+                interaction,
+                format!("No messages in <#{}> match those filters", channel),
+            )
+            .await;
+        }
+
+        let purged = delete(ctx, channel, messages).await;
+
+        let mut content = format!("Deleted {} messages in <#{}>", purged.deleted, channel);
+
+        if purged.deleted_singly > 0 {
+            content.push_str(&format!(
+                "\n{} of them were older than 14 days and had to be deleted one at a time",
+                purged.deleted_singly
+            ));
+        }
+
+        if purged.skipped > 0 {
+            content.push_str(&format!(
+                "\nSkipped {} more messages older than 14 days, only {} of those are deleted per purge",
+                purged.skipped, MAX_SINGLE_DELETES
+            ));
+        }
+
+        if purged.failed > 0 {
+            content.push_str(&format!("\nFailed to delete {} messages", purged.failed));
+        }
+
+        let mod_log_channel = handler
+            .cfg
+            .guild(guild.id.get())
+            .and_then(|settings| settings.mod_log_channel());
+
+        if let Some(mod_log_channel) = mod_log_channel {
+            let mut embed = CreateEmbed::new()
+                .title("Purge")
+                .field("Channel", format!("<#{}>", channel), true)
+                .field("Moderator", format!("<@{}>", interaction.user.id), true)
+                .field("Deleted", purged.deleted.to_string(), true)
+                .field("Filters", filter.describe(), false)
+                .timestamp(Timestamp::now());
+
+            if purged.skipped + purged.failed > 0 {
+                embed = embed.field(
+                    "Not deleted",
+                    format!("{} too old, {} failed", purged.skipped, purged.failed),
+                    false,
+                );
+            }
+
+            if let Err(err) = mod_log_channel
+                .send_message(&ctx.http, CreateMessage::new().embed(embed))
+                .await
+            {
+                error!("Failed to post purge to the mod log: {:?}", err);
+            }
+        }
+
+        edit_reply(ctx, interaction, content).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "count",
+                    "How many matching messages to delete",
+                )
+                .min_int_value(1)
+                .max_int_value(MAX_PURGE)
+                .required(true),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "Only delete messages from this user",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "links",
+                "Only delete messages containing links",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "attachments",
+                "Only delete messages with attachments",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "bots",
+                "Only delete messages from bots",
+            ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "The channel to purge, defaults to this one",
+                )
+                .channel_types(vec![
+                    ChannelType::Text,
+                    ChannelType::News,
+                    ChannelType::PublicThread,
+                    ChannelType::PrivateThread,
+                    ChannelType::Voice,
+                ]),
+            )
+            .default_member_permissions(Permissions::MANAGE_MESSAGES)
+            .dm_permission(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_messages() {
+        let mut message = Message::default();
+        message.author.id = UserId::new(1);
+        message.content = String::from("Join us at HTTPS://example.com");
+
+        assert!(PurgeFilter::default().matches(&message));
+
+        let links = PurgeFilter {
+            links: true,
+            user: Some(UserId::new(1)),
+            ..Default::default()
+        };
+        assert!(links.matches(&message));
+
+        let other_user = PurgeFilter {
+            user: Some(UserId::new(2)),
+            ..Default::default()
+        };
+        assert!(!other_user.matches(&message));
+
+        let bots = PurgeFilter {
+            bots: true,
+            ..Default::default()
+        };
+        assert!(!bots.matches(&message));
+
+        let attachments = PurgeFilter {
+            attachments: true,
+            ..Default::default()
+        };
+        assert!(!attachments.matches(&message));
+
+        message.pinned = true;
+        assert!(!PurgeFilter::default().matches(&message));
+    }
+}
@@ -0,0 +1,81 @@
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage},
+    client::Context,
+    model::application::{
+        CommandDataOption, CommandDataOptionValue, CommandInteraction, CommandOptionType,
+    },
+};
+
+use crate::{config::Visibility, event_handler::BotEvents};
+
+use super::CommandExecutionError;
+
+/// Name of the option lookup commands expose to post their reply publicly.
+const PUBLIC_OPTION: &str = "public";
+
+pub fn public_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::Boolean,
+        PUBLIC_OPTION,
+        "Show the result to everyone in the channel (staff only)",
+    )
+}
+
+fn requested_visibility(options: &[CommandDataOption]) -> Option<Visibility> {
+    options.iter().find_map(|option| match &option.value {
+        CommandDataOptionValue::Boolean(public) if option.name == PUBLIC_OPTION => {
+            Some(if *public {
+                Visibility::Public
+            } else {
+                Visibility::Ephemeral
+            })
+        }
+        CommandDataOptionValue::SubCommand(options)
+        | CommandDataOptionValue::SubCommandGroup(options) => requested_visibility(options),
+        _ => None,
+    })
+}
+
+/// Resolves whether a lookup reply may be seen by the whole channel. Replies are ephemeral unless
+/// the guild defaults to public, and only staff may ask for a public reply themselves.
+pub fn lookup_visibility(handler: &BotEvents, interaction: &CommandInteraction) -> Visibility {
+    let Some(settings) = interaction
+        .guild_id
+        .and_then(|guild_id| handler.cfg.guild(guild_id.get()))
+    else {
+        return Visibility::Ephemeral;
+    };
+
+    let is_staff = interaction.member.as_ref().is_some_and(|member| {
+        member
+            .roles
+            .iter()
+            .any(|role| settings.staff_roles.contains(&role.get()))
+    });
+
+    match requested_visibility(&interaction.data.options) {
+        Some(Visibility::Public) if is_staff => Visibility::Public,
+        Some(Visibility::Public) => Visibility::Ephemeral,
+        Some(Visibility::Ephemeral) => Visibility::Ephemeral,
+        None => settings.lookup_visibility,
+    }
+}
+
+/// Replies to a lookup command, applying the guild's visibility policy.
+pub async fn respond_lookup(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    message: CreateInteractionResponseMessage,
+) -> Result<(), CommandExecutionError> {
+    let ephemeral = lookup_visibility(handler, interaction) == Visibility::Ephemeral;
+
+    interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(message.ephemeral(ephemeral)),
+        )
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+}
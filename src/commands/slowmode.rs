@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{CreateCommand, CreateCommandOption, EditChannel},
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        channel::ChannelType,
+        Permissions,
+    },
+};
+
+use crate::{duration, event_handler::BotEvents};
+
+use super::{
+    moderation::{channel_permissions, moderation_context, reply_ephemeral},
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Choices offered by `/slowmode`, in seconds. Discord allows at most 6 hours.
+const PRESETS: [(&str, i32); 12] = [
+    ("Off", 0),
+    ("5 seconds", 5),
+    ("10 seconds", 10),
+    ("30 seconds", 30),
+    ("1 minute", 60),
+    ("2 minutes", 2 * 60),
+    ("5 minutes", 5 * 60),
+    ("10 minutes", 10 * 60),
+    ("30 minutes", 30 * 60),
+    ("1 hour", 60 * 60),
+    ("2 hours", 2 * 60 * 60),
+    ("6 hours", 6 * 60 * 60),
+];
+
+#[derive(Debug)]
+pub struct SlowmodeCommand;
+
+impl CommandInfo for SlowmodeCommand {
+    fn name(&self) -> String {
+        String::from("slowmode")
+    }
+
+    fn description(&self) -> String {
+        String::from("Limit how often members can send messages in a channel")
+    }
+}
+
+#[async_trait]
+impl Command for SlowmodeCommand {
+    async fn execute<'a>(
+        &self,
+        _: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut seconds = None;
+        let mut channel = interaction.channel_id;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("duration", CommandDataOptionValue::Integer(value)) => {
+                    seconds = u16::try_from(*value).ok()
+                }
+                ("channel", CommandDataOptionValue::Channel(channel_id)) => channel = *channel_id,
+                _ => {}
+            }
+        }
+
+        let Some(seconds) = seconds else {
+            return Err(
+                Report::from(CommandExecutionError).attach_printable("Failed to get duration arg")
+            );
+        };
+
+        let (guild, member) = moderation_context(ctx, interaction).await?;
+        let permissions = channel_permissions(ctx, &guild, member, channel).await?;
+
+        if !permissions.contains(Permissions::VIEW_CHANNEL | Permissions::MANAGE_CHANNELS) {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!(
+                    "You need the View Channel and Manage Channels permissions in <#{}> to change its slowmode",
+                    channel
+                ),
+            )
+            .await;
+        }
+
+        let reason = format!("Slowmode set by {}", interaction.user.name);
+
+        channel
+            .edit(
+                &ctx.http,
+                EditChannel::new()
+                    .rate_limit_per_user(seconds)
+                    .audit_log_reason(&reason),
+            )
+            .await
+            .map_err(|e| {
+                Report::from(e)
+                    .change_context(CommandExecutionError)
+                    .attach_printable("Failed to set slowmode")
+            })?;
+
+        let content = if seconds == 0 {
+            format!("Turned off slowmode in <#{}>", channel)
+        } else {
+            format!(
+                "Members can now send a message in <#{}> once every {}",
+                channel,
+                duration::format(Duration::from_secs(seconds.into()))
+            )
+        };
+
+        reply_ephemeral(ctx, interaction, content).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        let mut duration = CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "duration",
+            "How long members have to wait between messages",
+        )
+        .required(true);
+
+        for (name, seconds) in PRESETS {
+            duration = duration.add_int_choice(name, seconds);
+        }
+
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(duration)
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "The channel to change, defaults to this one",
+                )
+                .channel_types(vec![
+                    ChannelType::Text,
+                    ChannelType::News,
+                    ChannelType::Voice,
+                    ChannelType::Forum,
+                    ChannelType::PublicThread,
+                    ChannelType::PrivateThread,
+                ]),
+            )
+            .default_member_permissions(Permissions::MANAGE_CHANNELS)
+            .dm_permission(false)
+    }
+}
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::all::{
+    CommandDataOptionValue, CommandInteraction, CommandOptionType, Context, CreateCommand,
+    CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage,
+    EditInteractionResponse, Permissions,
+};
+use tracing::error;
+
+use crate::{event_handler::BotEvents, role_sync};
+
+use super::{Command, CommandExecutionError, CommandInfo};
+
+#[derive(Debug)]
+pub struct SyncRolesCommand;
+
+impl CommandInfo for SyncRolesCommand {
+    fn name(&self) -> String {
+        String::from("syncroles")
+    }
+
+    fn description(&self) -> String {
+        String::from("Syncs verified and addon roles with Pulsar Link")
+    }
+}
+
+#[async_trait]
+impl Command for SyncRolesCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Command used outside of a guild"));
+        };
+
+        let target = match interaction.data.options.first() {
+            Some(option) => match option.value {
+                CommandDataOptionValue::User(user_id) => user_id,
+                _ => {
+                    return Err(Report::from(CommandExecutionError)
+                        .attach_printable("Failed to get target user arg"))
+                }
+            },
+            None => interaction.user.id,
+        };
+
+        let can_manage_roles = interaction
+            .member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .is_some_and(|permissions| permissions.manage_roles());
+
+        let settings = handler.cfg.guild(guild_id.get());
+
+        let refusal = match settings {
+            None => Some("Role sync isn't configured for this server."),
+            Some(_) if target != interaction.user.id && !can_manage_roles => {
+                Some("You need the Manage Roles permission to sync another user's roles.")
+            }
+            Some(_) => None,
+        };
+
+        if let Some(refusal) = refusal {
+            let message = CreateInteractionResponseMessage::new()
+                .content(refusal)
+                .ephemeral(true);
+
+            interaction
+                .create_response(&ctx.http, CreateInteractionResponse::Message(message))
+                .await
+                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+            return Ok(());
+        }
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let member = guild_id
+            .member(&ctx, target)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let content = match role_sync::sync_member(
+            &ctx.http,
+            &handler.api,
+            settings.unwrap(),
+            &member,
+            false,
+        )
+        .await
+        {
+            Ok(outcome) if !outcome.linked && outcome.is_unchanged() => {
+                format!("<@{}> isn't linked to Pulsar Link.", target)
+            }
+            Ok(outcome) => outcome.describe(),
+            Err(err) => {
+                error!("Failed to sync roles for {}: {:?}", target, err);
+
+                String::from("An error occurred while syncing roles. Please try again later.")
+            }
+        };
+
+        interaction
+            .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        Ok(())
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "user",
+                "The user to sync, defaults to yourself",
+            ))
+            .default_member_permissions(Permissions::SEND_MESSAGES)
+            .dm_permission(false)
+    }
+}
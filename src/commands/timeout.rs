@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{CreateCommand, CreateCommandOption, EditMember},
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        id::{GuildId, UserId},
+        Permissions, Timestamp,
+    },
+};
+use tracing::error;
+
+use crate::{
+    database::cases::{Case, CaseAction, NewCase},
+    duration::{self, unix_now},
+    event_handler::BotEvents,
+};
+
+use super::{
+    moderation::{
+        describe_case, describe_notice, edit_reply, find_member, moderation_context, notice,
+        notify, record_case, refusal, reply_ephemeral, should_notify,
+    },
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Longest timeout Discord allows.
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(28 * 24 * 60 * 60);
+
+#[derive(Debug)]
+pub struct TimeoutCommand;
+
+impl CommandInfo for TimeoutCommand {
+    fn name(&self) -> String {
+        String::from("timeout")
+    }
+
+    fn description(&self) -> String {
+        String::from("Stop a user from talking for a while")
+    }
+}
+
+/// Times out a member for `length` and records it.
+pub async fn apply_timeout(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild_id: GuildId,
+    moderator_id: UserId,
+    target: UserId,
+    length: Duration,
+    reason: Option<&str>,
+) -> Result<Option<Case>, CommandExecutionError> {
+    let Ok(until) = Timestamp::from_unix_timestamp((unix_now() + length.as_secs()) as i64) else {
+        return Err(
+            Report::from(CommandExecutionError).attach_printable("Timeout end is out of range")
+        );
+    };
+
+    let mut builder = EditMember::new().disable_communication_until_datetime(until);
+
+    if let Some(reason) = reason {
+        builder = builder.audit_log_reason(reason);
+    }
+
+    guild_id
+        .edit_member(ctx, target, builder)
+        .await
+        .map_err(|e| {
+            Report::from(e)
+                .change_context(CommandExecutionError)
+                .attach_printable("Failed to timeout user")
+        })?;
+
+    Ok(record_case(
+        handler,
+        ctx,
+        NewCase {
+            guild_id: guild_id.get(),
+            action: CaseAction::Timeout,
+            target_id: target.get(),
+            moderator_id: moderator_id.get(),
+            reason: reason.map(String::from),
+            duration: Some(length),
+        },
+    )
+    .await)
+}
+
+#[async_trait]
+impl Command for TimeoutCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut target = None;
+        let mut duration_arg = None;
+        let mut reason = None;
+        let mut notify_arg = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::User(user_id)) => target = Some(*user_id),
+                ("duration", CommandDataOptionValue::String(duration)) => {
+                    duration_arg = Some(duration.as_str())
+                }
+                ("reason", CommandDataOptionValue::String(timeout_reason)) => {
+                    reason = Some(timeout_reason.as_str())
+                }
+                ("notify", CommandDataOptionValue::Boolean(notify)) => notify_arg = Some(*notify),
+                _ => {}
+            }
+        }
+
+        let (Some(target), Some(duration_arg)) = (target, duration_arg) else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Failed to get target user or duration arg"));
+        };
+
+        let Some(length) = duration::parse(duration_arg) else {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!(
+                    "`{}` isn't a valid duration. Use something like `10m`, `12h` or `1d12h`.",
+                    duration_arg
+                ),
+            )
+            .await;
+        };
+
+        if length > MAX_TIMEOUT {
+            return reply_ephemeral(ctx, interaction, "Timeouts can last at most 28 days").await;
+        }
+
+        let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+
+        let Some(target_member) = find_member(ctx, guild.id, target).await? else {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!("<@{}> isn't in this server", target),
+            )
+            .await;
+        };
+
+        if let Some(refusal) = refusal(
+            &guild,
+            interaction_member,
+            target,
+            Some(&target_member),
+            "timeout",
+        ) {
+            return reply_ephemeral(ctx, interaction, refusal).await;
+        }
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let until = unix_now() + length.as_secs();
+        let case = apply_timeout(
+            handler,
+            ctx,
+            guild.id,
+            interaction.user.id,
+            target,
+            length,
+            reason,
+        )
+        .await;
+
+        let case = match case {
+            Ok(case) => case,
+            Err(err) => {
+                error!("Failed to time out {} in {}: {:?}", target, guild.id, err);
+
+                return edit_reply(
+                    ctx,
+                    interaction,
+                    format!("❌ <@{}>: failed to time out", target),
+                )
+                .await;
+            }
+        };
+
+        let notified = if should_notify(handler, guild.id, notify_arg) {
+            let notice = notice(format!("You have been timed out in {}", guild.name), reason)
+                .field("Ends", format!("<t:{}:R>", until), false);
+
+            Some(notify(ctx, target, notice).await)
+        } else {
+            None
+        };
+
+        let user = &target_member.user;
+
+        edit_reply(
+            ctx,
+            interaction,
+            format!(
+                "Timed out {} for {}{}{}",
+                user.global_name.as_deref().unwrap_or(&user.name),
+                duration::format(length),
+                describe_case(case.as_ref()),
+                describe_notice(notified, "timeout")
+            ),
+        )
+        .await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::User, "user", "The user to time out")
+                    .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "duration",
+                    "How long the timeout lasts, up to 28 days, e.g. 10m or 12h",
+                )
+                .required(true),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "reason",
+                "Reason for the timeout",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "notify",
+                "DM the user about the timeout, defaults to the server's setting",
+            ))
+            .default_member_permissions(Permissions::MODERATE_MEMBERS)
+            .dm_permission(false)
+    }
+}
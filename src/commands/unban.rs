@@ -0,0 +1,357 @@
+use async_trait::async_trait;
+use error_stack::{Report, Result};
+use serenity::{
+    builder::{
+        AutocompleteChoice, CreateAutocompleteResponse, CreateCommand, CreateCommandOption,
+        CreateInteractionResponse,
+    },
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        guild::Ban,
+        id::{GuildId, UserId},
+        Permissions,
+    },
+};
+
+use tracing::{error, warn};
+
+use crate::{
+    database::{
+        cases::{Case, CaseAction, NewCase},
+        tempbans::TempBan,
+    },
+    event_handler::BotEvents,
+};
+
+use super::{
+    moderation::{
+        describe_case, edit_reply, find_member, guild_name, moderation_context, parse_user_id,
+        record_case, refusal, reply_ephemeral, sync_guild, sync_guilds, synced_reason, syncs_bans,
+    },
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Discord shows at most this many autocomplete suggestions.
+const MAX_SUGGESTIONS: usize = 25;
+
+/// Longest name Discord accepts for an autocomplete suggestion.
+const MAX_SUGGESTION_LENGTH: usize = 100;
+
+#[derive(Debug)]
+pub struct UnbanCommand;
+
+impl CommandInfo for UnbanCommand {
+    fn name(&self) -> String {
+        String::from("unban")
+    }
+
+    fn description(&self) -> String {
+        String::from("Unban a user from the server")
+    }
+}
+
+pub struct Unbanned {
+    pub name: String,
+    pub case: Option<Case>,
+    /// The temporary ban that no longer needs lifting, if there was one.
+    pub cancelled: Option<TempBan>,
+    /// Why a pending temporary ban may still be around, when clearing it failed.
+    pub schedule_problem: Option<&'static str>,
+}
+
+/// Lifts a user's ban in a guild and records it, or returns `None` if they aren't banned there.
+/// Once Discord has lifted the ban, failing to clear its temporary ban is logged and reported
+/// rather than returned.
+pub async fn lift_ban(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild_id: GuildId,
+    moderator_id: UserId,
+    target: UserId,
+    reason: Option<&str>,
+) -> Result<Option<Unbanned>, CommandExecutionError> {
+    let ban = ctx
+        .http
+        .get_ban(guild_id, target)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let Some(ban) = ban else {
+        return Ok(None);
+    };
+
+    ctx.http
+        .remove_ban(guild_id, target, reason)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let (cancelled, schedule_problem) = match handler
+        .db
+        .remove_tempban(guild_id.get(), target.get())
+    {
+        Ok(cancelled) => (cancelled, None),
+        Err(err) => {
+            error!(
+                "Unbanned {} in {} but failed to cancel their temporary ban: {:?}",
+                target, guild_id, err
+            );
+            (
+                    None,
+                    Some("The unban went through, but their temporary ban couldn't be cleared from the database."),
+                )
+        }
+    };
+
+    let case = record_case(
+        handler,
+        ctx,
+        NewCase {
+            guild_id: guild_id.get(),
+            action: CaseAction::Unban,
+            target_id: target.get(),
+            moderator_id: moderator_id.get(),
+            reason: reason.map(String::from),
+            duration: None,
+        },
+    )
+    .await;
+
+    Ok(Some(Unbanned {
+        name: ban.user.global_name.unwrap_or(ban.user.name),
+        case,
+        cancelled,
+        schedule_problem,
+    }))
+}
+
+fn suggestion(ban: &Ban) -> AutocompleteChoice {
+    let mut name = format!("{} ({})", ban.user.name, ban.user.id);
+
+    if let Some(reason) = &ban.reason {
+        name.push_str(&format!(" - {}", reason));
+    }
+
+    if name.chars().count() > MAX_SUGGESTION_LENGTH {
+        name = name.chars().take(MAX_SUGGESTION_LENGTH - 3).collect();
+        name.push_str("...");
+    }
+
+    AutocompleteChoice::new(name, ban.user.id.to_string())
+}
+
+#[async_trait]
+impl Command for UnbanCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let mut user_arg = None;
+        let mut reason = None;
+        let mut all_guilds = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::String(user)) => user_arg = Some(user.as_str()),
+                ("reason", CommandDataOptionValue::String(unban_reason)) => {
+                    reason = Some(unban_reason.as_str())
+                }
+                ("all_guilds", CommandDataOptionValue::Boolean(all)) => all_guilds = Some(*all),
+                _ => {}
+            }
+        }
+
+        let Some(user_arg) = user_arg else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Failed to get target user arg"));
+        };
+
+        let Some(target) = parse_user_id(user_arg) else {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!("`{}` isn't a valid user ID", user_arg),
+            )
+            .await;
+        };
+
+        let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+
+        // Banned users are rarely still members, but the hierarchy check applies if they are
+        let target_member = find_member(ctx, guild.id, target).await?;
+
+        if let Some(refusal) = refusal(
+            &guild,
+            interaction_member,
+            target,
+            target_member.as_ref(),
+            "unban",
+        ) {
+            return reply_ephemeral(ctx, interaction, refusal).await;
+        }
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let mut content =
+            match lift_ban(handler, ctx, guild.id, interaction.user.id, target, reason).await? {
+                Some(unbanned) => {
+                    let mut content = format!(
+                        "Unbanned {}{}",
+                        unbanned.name,
+                        describe_case(unbanned.case.as_ref())
+                    );
+
+                    if let Some(tempban) = unbanned.cancelled {
+                        content.push_str(&format!(
+                            "\nCancelled their temporary ban, which would have expired <t:{}:R>",
+                            tempban.unban_at
+                        ));
+                    }
+
+                    if let Some(problem) = unbanned.schedule_problem {
+                        content.push_str(&format!("\n⚠️ {}", problem));
+                    }
+
+                    content
+                }
+                None => format!("<@{}> isn't banned here", target),
+            };
+
+        if syncs_bans(handler, all_guilds) {
+            let reason = synced_reason(&guild, reason);
+            let mut results = vec![];
+
+            for guild_id in sync_guilds(handler, guild.id) {
+                let name = guild_name(ctx, guild_id);
+
+                let synced = match sync_guild(ctx, guild_id, interaction.user.id).await {
+                    Ok(synced) => synced,
+                    Err(skipped) => {
+                        results.push(format!("❌ {}: {}", name, skipped));
+                        continue;
+                    }
+                };
+
+                if let Some(refusal) = synced.refusal(ctx, target, "unban").await {
+                    results.push(format!("❌ {}: {}", name, refusal));
+                    continue;
+                }
+
+                match lift_ban(
+                    handler,
+                    ctx,
+                    guild_id,
+                    interaction.user.id,
+                    target,
+                    Some(&reason),
+                )
+                .await
+                {
+                    Ok(Some(unbanned)) => {
+                        let mut line =
+                            format!("✅ {}{}", name, describe_case(unbanned.case.as_ref()));
+
+                        if let Some(problem) = unbanned.schedule_problem {
+                            line.push_str(&format!("\n⚠️ {}", problem));
+                        }
+
+                        results.push(line);
+                    }
+                    Ok(None) => results.push(format!("➖ {}: not banned", name)),
+                    Err(err) => {
+                        warn!(
+                            "Failed to sync unban of {} to {}: {:?}",
+                            target, guild_id, err
+                        );
+                        results.push(format!("❌ {}: failed to unban", name));
+                    }
+                }
+            }
+
+            if !results.is_empty() {
+                content.push_str("\n\nOther servers:\n");
+                content.push_str(&results.join("\n"));
+            }
+        }
+
+        edit_reply(ctx, interaction, content).await
+    }
+
+    async fn autocomplete(
+        &self,
+        _handler: &BotEvents,
+        ctx: &Context,
+        interaction: &CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Ok(());
+        };
+
+        let query = interaction
+            .data
+            .autocomplete()
+            .map(|option| option.value.to_lowercase())
+            .unwrap_or_default();
+
+        let bans = guild_id
+            .bans(&ctx.http, None, None)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let choices = bans
+            .iter()
+            .filter(|ban| {
+                ban.user.name.to_lowercase().contains(&query)
+                    || ban
+                        .user
+                        .global_name
+                        .as_ref()
+                        .is_some_and(|name| name.to_lowercase().contains(&query))
+                    || ban.user.id.to_string().starts_with(&query)
+            })
+            .take(MAX_SUGGESTIONS)
+            .map(suggestion)
+            .collect();
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Autocomplete(
+                    CreateAutocompleteResponse::new().set_choices(choices),
+                ),
+            )
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "user",
+                    "The banned user, by ID or picked from the ban list",
+                )
+                .required(true)
+                .set_autocomplete(true),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "reason",
+                "Reason to unban the user",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "all_guilds",
+                "Unban them in every partner server too, defaults to the ban sync setting",
+            ))
+            .default_member_permissions(Permissions::BAN_MEMBERS)
+            .dm_permission(false)
+    }
+}
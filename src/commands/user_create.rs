@@ -1,12 +1,17 @@
 use async_trait::async_trait;
-use error_stack::{IntoReport, Report, Result, ResultExt};
-use serde::Serialize;
+use error_stack::{Report, Result};
+use reqwest::StatusCode;
 use serenity::all::{
-    CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
-    CreateInteractionResponse, CreateInteractionResponseMessage, Permissions,
+    CommandDataOptionValue, CommandInteraction, CommandOptionType, Context, CreateCommand,
+    CreateCommandOption, EditInteractionResponse, Permissions, UserId,
 };
+use tracing::error;
 
-use crate::event_handler::BotEvents;
+use crate::{
+    api::UserLookup,
+    event_handler::BotEvents,
+    models::{CreateUser, ErrorResponse, User},
+};
 
 use super::{Command, CommandExecutionError, CommandInfo};
 
@@ -23,11 +28,33 @@ impl CommandInfo for UserCreateCommand {
     }
 }
 
-#[derive(Serialize)]
-struct CreateUserBody {
-    steam_id: u64,
-    gmodstore_id: String,
-    discord_id: u64,
+fn describe_failure(status: StatusCode, body: ErrorResponse) -> String {
+    let reason = body
+        .error
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or("Unknown error").to_string());
+
+    match status {
+        StatusCode::CONFLICT => match body.id {
+            Some(id) => format!("This user is already linked to Pulsar ID `{}`.", id),
+            None => format!("This user is already linked: {}", reason),
+        },
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+            let mut content = format!("The backend rejected the user: {}", reason);
+
+            for (field, message) in &body.errors {
+                content.push_str(&format!("\n- `{}`: {}", field, message));
+            }
+
+            content
+        }
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => String::from(
+            "The bot is not authorised to create users. Check the `api-key` in the bot's config.",
+        ),
+        _ => format!(
+            "An error occurred while creating the user ({}): {}",
+            status, reason
+        ),
+    }
 }
 
 #[async_trait]
@@ -38,94 +65,44 @@ impl Command for UserCreateCommand {
         ctx: &Context,
         interaction: &'a mut CommandInteraction,
     ) -> Result<(), CommandExecutionError> {
-        let user = match interaction.data.options.get(0) {
-            Some(target_command_data) => target_command_data,
-            None => {
-                return Err(Report::from(CommandExecutionError)
-                    .attach_printable("Failed to get command arg data user"))
+        let mut discord_id = None;
+        let mut steam_id = None;
+        let mut gmodstore_id = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::User(user_id)) => discord_id = Some(user_id.get()),
+                ("steam-id", CommandDataOptionValue::String(value)) => steam_id = Some(value),
+                ("gmodstore-id", CommandDataOptionValue::String(value)) => {
+                    gmodstore_id = Some(value)
+                }
+                _ => {}
             }
-        };
-
-        let steam_id = match interaction.data.options.get(1) {
-            Some(target_command_data) => target_command_data,
-            None => {
-                return Err(Report::from(CommandExecutionError)
-                    .attach_printable("Failed to get command arg data user"))
-            }
-        };
-
-        let gmodstore_id = match interaction.data.options.get(2) {
-            Some(target_command_data) => target_command_data,
-            None => {
-                return Err(Report::from(CommandExecutionError)
-                    .attach_printable("Failed to get command arg data user"))
-            }
-        };
-
-        let client = reqwest::Client::new();
-
-        let discord_id = user.value.as_user_id().unwrap().get();
-        let steam_id = {
-            let sid_str = steam_id.value.as_str().ok_or(Report::from(CommandExecutionError).attach_printable("Failed to extract Steam ID from value"))?;
-
-            println!("{sid_str}");
-
-            sid_str.parse::<u64>().map_err(|e| Report::from(e).change_context(CommandExecutionError))?
-        };
+        }
 
-        let gmodstore_id = {
-            gmodstore_id.value.as_str().ok_or(Report::from(CommandExecutionError).attach_printable("Failed to extract Steam ID from value"))?
+        let (Some(discord_id), Some(steam_id), Some(gmodstore_id)) =
+            (discord_id, steam_id, gmodstore_id)
+        else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Failed to get command arg data"));
         };
 
-        let params = CreateUserBody {
-            steam_id,
-            gmodstore_id: gmodstore_id.to_string(),
-            discord_id,
-        };
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
 
-        let url = format!("{}/user", handler.cfg.api_url);
-        let params = match serde_urlencoded::to_string(&params) {
-            Ok(string) => string,
-            Err(error) => return Err(Report::from(error).change_context(CommandExecutionError))
+        let content = match steam_id.trim().parse::<u64>() {
+            Ok(steam_id) => {
+                create_user(handler, discord_id, steam_id, gmodstore_id.trim().to_string()).await
+            }
+            Err(_) => format!("`{}` is not a valid SteamID64.", steam_id),
         };
 
-        let url = format!("{}?{}", url, params);
-
-        let res = client
-            .post(url)
-            .header("Authorization", handler.cfg.api_key.as_str())
-            .send()
-            .await;
-
-        if let Ok(_res) = res {
-            let message = CreateInteractionResponseMessage::new()
-                .content("Successfully created user.")
-                .ephemeral(true);
-
-            let builder = CreateInteractionResponse::Message(message);
-
-            interaction
-                .create_response(&ctx.http, builder)
-                .await
-                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-
-            return Ok(());
-        } else if let Err(e) = res {
-            let message = CreateInteractionResponseMessage::new()
-                .content("An error occurred. Please try again later.")
-                .ephemeral(true);
-
-            let builder = CreateInteractionResponse::Message(message);
-
-            interaction
-                .create_response(&ctx.http, builder)
-                .await
-                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-
-            println!("Error occurred while creating user: {}", e);
-
-            return Ok(());
-        }
+        interaction
+            .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
 
         Ok(())
     }
@@ -157,3 +134,108 @@ impl Command for UserCreateCommand {
             .dm_permission(false)
     }
 }
+
+/// Checks none of the identities are linked yet, then creates the user. Returns the message
+/// shown to the invoking staff member.
+async fn create_user(
+    handler: &BotEvents,
+    discord_id: u64,
+    steam_id: u64,
+    gmodstore_id: String,
+) -> String {
+    let steam = steam_id.to_string();
+    let identities = [
+        (
+            "Discord account",
+            UserLookup::Discord(UserId::new(discord_id)),
+            discord_id.to_string(),
+        ),
+        ("Steam ID", UserLookup::Steam(&steam), steam.clone()),
+        (
+            "Gmodstore ID",
+            UserLookup::Gmodstore(&gmodstore_id),
+            gmodstore_id.clone(),
+        ),
+    ];
+
+    let mut existing = vec![];
+
+    for (label, lookup, id) in identities {
+        match handler.api.find_user(lookup).await {
+            Ok(Some(User {
+                id: Some(pulsar_id),
+                ..
+            })) => existing.push(format!(
+                "- {} `{}` is already linked to Pulsar ID `{}`",
+                label, id, pulsar_id
+            )),
+            Ok(_) => {}
+            Err(err) => {
+                error!("Failed to look up {} {}: {:?}", label, id, err);
+
+                return format!(
+                    "Unable to check whether the {} is already linked. Please try again later.",
+                    label
+                );
+            }
+        }
+    }
+
+    if !existing.is_empty() {
+        return format!(
+            "The user was not created because an existing link was found:\n{}",
+            existing.join("\n")
+        );
+    }
+
+    let params = CreateUser {
+        steam_id,
+        gmodstore_id,
+        discord_id,
+    };
+
+    let params = match serde_urlencoded::to_string(&params) {
+        Ok(string) => string,
+        Err(e) => {
+            error!("Failed to encode user create params: {}", e);
+
+            return String::from("An error occurred. Please try again later.");
+        }
+    };
+
+    let url = format!("{}/user?{}", handler.cfg.api_url, params);
+
+    let response = match handler
+        .http
+        .post(url)
+        .header("Authorization", handler.cfg.api_key.as_str())
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Error occurred while creating user: {}", e);
+
+            return String::from("An error occurred. Please try again later.");
+        }
+    };
+
+    let status = response.status();
+
+    if status.is_success() {
+        return match response.json::<User>().await {
+            Ok(User { id: Some(id), .. }) => {
+                format!("Successfully created user with Pulsar ID `{}`.", id)
+            }
+            _ => String::from("Successfully created user."),
+        };
+    }
+
+    let body = response.json::<ErrorResponse>().await.unwrap_or_default();
+
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        error!("Backend rejected the API key while creating a user ({})", status);
+    }
+
+    describe_failure(status, body)
+}
@@ -1,16 +1,19 @@
 use async_trait::async_trait;
-use error_stack::{IntoReport, Report, Result, ResultExt};
-use serde::Deserialize;
+use error_stack::{Report, Result};
 use serenity::all::{
     CommandDataOptionValue, CommandInteraction,
-    CommandOptionType, Context, CreateCommand, CreateCommandOption, CreateInteractionResponse,
+    CommandOptionType, Context, CreateCommand, CreateCommandOption,
     CreateInteractionResponseMessage,
 };
 use serenity::all::CommandDataOptionValue::SubCommand;
+use tracing::error;
 
-use crate::event_handler::BotEvents;
+use crate::{api::UserLookup, event_handler::BotEvents};
 
-use super::{Command, CommandExecutionError, CommandInfo};
+use super::{
+    response::{public_option, respond_lookup},
+    Command, CommandExecutionError, CommandInfo,
+};
 
 #[derive(Debug)]
 pub struct UserGetCommand;
@@ -25,13 +28,34 @@ impl CommandInfo for UserGetCommand {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct User {
-    id: Option<String>,
-    steamId: Option<u64>,
-    gmodstoreId: Option<String>,
-    discordId: Option<u64>,
-    error: Option<String>,
+/// Looks a user up in Pulsar Link and formats the reply, shared with the context menus.
+pub async fn describe_user(
+    handler: &BotEvents,
+    lookup: UserLookup<'_>,
+) -> CreateInteractionResponseMessage {
+    let user = match handler.api.find_user(lookup).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            return CreateInteractionResponseMessage::new()
+                .content("No Pulsar Link user was found.");
+        }
+        Err(err) => {
+            error!("Failed to look up user: {:?}", err);
+
+            return CreateInteractionResponseMessage::new()
+                .content("An error occurred while trying to get the user. Please try again later.");
+        }
+    };
+
+    let or_unknown = |value: Option<String>| value.unwrap_or_else(|| String::from("Unknown"));
+    let steam_id = or_unknown(user.steam_id.map(|id| id.to_string()));
+    let gmodstore_id = or_unknown(user.gmodstore_id);
+    let discord_id = or_unknown(user.discord_id.map(|id| id.to_string()));
+
+    CreateInteractionResponseMessage::new()
+        .content(format!(
+            "- Pulsar ID: {}\n- Steam ID: [{}](<https://steamcommunity.com/id/{}/>)\n- Gmodstore ID: [{}](<https://www.gmodstore.com/users/{}>)\n- Discord ID: [{}](<https://discord.com/users/{}>)
+            ", or_unknown(user.id), steam_id, steam_id, gmodstore_id, gmodstore_id, discord_id, discord_id))
 }
 
 #[async_trait]
@@ -42,7 +66,7 @@ impl Command for UserGetCommand {
         ctx: &Context,
         interaction: &'a mut CommandInteraction,
     ) -> Result<(), CommandExecutionError> {
-        let sub_cmd = match interaction.data.options.get(0) {
+        let sub_cmd = match interaction.data.options.first() {
             Some(target_command_data) => target_command_data,
             None => {
                 return Err(Report::from(CommandExecutionError)
@@ -50,11 +74,9 @@ impl Command for UserGetCommand {
             }
         };
 
-        let command_type = sub_cmd.name.as_str();
-
         let sub_command_data = match &sub_cmd.value {
-            SubCommand(options) => match options.get(0) {
-                Some(command_data_option) => command_data_option.clone(),
+            SubCommand(options) => match options.iter().find(|option| option.name == "id") {
+                Some(command_data_option) => &command_data_option.value,
                 None => {
                     return Err(Report::from(CommandExecutionError)
                         .attach_printable("Failed to get sub command arg data"));
@@ -66,99 +88,20 @@ impl Command for UserGetCommand {
             }
         };
 
-        let id;
-        match command_type {
-            "discord" => {
-                let target_user = match sub_command_data.value {
-                    CommandDataOptionValue::User(user_id) => user_id,
-                    _ => {
-                        return Err(Report::from(CommandExecutionError)
-                            .attach_printable("Failed to get target user arg"));
-                    }
-                };
-
-                let target_user = target_user.to_user(&ctx.http).await.map_err(|e| {
-                    Report::from(e)
-                        .change_context(CommandExecutionError)
-                        .attach_printable("Failed to fetch user")
-                })?;
-
-                id = target_user.id.to_string();
-            }
-            _ => {
-                id = sub_command_data.value.as_str().unwrap().to_string();
-            }
-        }
-
-        let url;
-        match command_type {
-            "pulsar-id" => {
-                url = format!("{}/user/{}", handler.cfg.api_url, id);
-            }
-            "discord" => {
-                url = format!("{}/user/{}/discord", handler.cfg.api_url, id);
-            }
-            "steam-id" => {
-                url = format!("{}/user/{}/steam", handler.cfg.api_url, id);
-            }
-            "gmodstore-id" => {
-                url = format!("{}/user/{}/gmodstore", handler.cfg.api_url, id);
-            }
+        let lookup = match (sub_cmd.name.as_str(), sub_command_data) {
+            ("discord", CommandDataOptionValue::User(user_id)) => UserLookup::Discord(*user_id),
+            ("pulsar-id", CommandDataOptionValue::String(id)) => UserLookup::PulsarId(id),
+            ("steam-id", CommandDataOptionValue::String(id)) => UserLookup::Steam(id),
+            ("gmodstore-id", CommandDataOptionValue::String(id)) => UserLookup::Gmodstore(id),
             _ => {
                 return Err(Report::from(CommandExecutionError)
                     .attach_printable("Invalid sub command type"));
             }
-        }
-
-        let client = reqwest::Client::new();
-        let response = client
-            .get(url.to_owned())
-            .header("Authorization", handler.cfg.api_key.as_str())
-            .send()
-            .await
-            .expect("Failed to send request");
-
-        let response_text = response.text().await.expect("Failed to get response text");
-
-        let response: Result<User, _> =
-            serde_json::from_str(&response_text).map_err(|err| error_stack::Report::from(err));
-        let user = response.unwrap();
-
-        if user.error != None {
-            let message = CreateInteractionResponseMessage::new().content(format!(
-                "An error occurred while trying to get the user: {}",
-                user.error.unwrap()
-            ));
-
-            let builder = CreateInteractionResponse::Message(message);
-
-            interaction
-                .create_response(&ctx.http, builder)
-                .await
-                .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
-
-            return Ok(());
-        }
-
-        println!("{:?}", user);
-
-        let steam_id = user.steamId.unwrap();
-        let gmodstore_id = user.gmodstoreId.unwrap();
-        let discord_id = user.discordId.unwrap();
-
-        let message = CreateInteractionResponseMessage::new()
-            .content(format!(
-                "- Pulsar ID: {}\n- Steam ID: [{}](<https://steamcommunity.com/id/{}/>)\n- Gmodstore ID: [{}](<https://www.gmodstore.com/users/{}>)\n- Discord ID: [{}](<https://discord.com/users/{}>)
-            ", user.id.unwrap(), steam_id, steam_id, gmodstore_id, gmodstore_id, discord_id, discord_id));
-
-        let builder = CreateInteractionResponse::Message(message);
+        };
 
-        interaction
-            .create_response(&ctx.http, builder)
-            .await
-            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+        let message = describe_user(handler, lookup).await;
 
-        return Ok(());
+        respond_lookup(handler, ctx, interaction, message).await
     }
 
     async fn register(&self, _: &BotEvents) -> CreateCommand {
@@ -177,7 +120,8 @@ impl Command for UserGetCommand {
                             "The users Pulsar ID.",
                         )
                             .required(true),
-                    ),
+                    )
+                    .add_sub_option(public_option()),
             )
             .add_option(
                 CreateCommandOption::new(
@@ -192,7 +136,8 @@ impl Command for UserGetCommand {
                             "The users Discord account.",
                         )
                             .required(true),
-                    ),
+                    )
+                    .add_sub_option(public_option()),
             )
             .add_option(
                 CreateCommandOption::new(
@@ -207,7 +152,8 @@ impl Command for UserGetCommand {
                             "The users SteamID64.",
                         )
                             .required(true),
-                    ),
+                    )
+                    .add_sub_option(public_option()),
             )
             .add_option(
                 CreateCommandOption::new(
@@ -222,7 +168,8 @@ impl Command for UserGetCommand {
                             "The users Gmodstore ID.",
                         )
                             .required(true),
-                    ),
+                    )
+                    .add_sub_option(public_option()),
             )
             .dm_permission(false)
     }
@@ -2,17 +2,75 @@ use async_trait::async_trait;
 use error_stack::{Report, Result};
 use serenity::{
     builder::{
-        CreateCommand, CreateInteractionResponse,
-        CreateInteractionResponseMessage,
+        CreateActionRow, CreateAllowedMentions, CreateButton, CreateCommand,
+        CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage, EditInteractionResponse,
     },
     client::Context,
-    model::application::CommandInteraction,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        id::UserId,
+    },
 };
+use tracing::error;
 
-use crate::event_handler::BotEvents;
+use crate::{config::VerifySettings, event_handler::BotEvents, models::User, role_sync};
 
 use super::{Command, CommandExecutionError, CommandInfo};
 
+const DEFAULT_TEMPLATE: &str = "To gain access to support channels you first have to verify your Discord account.\nTo do this, please follow these steps:\nThey can also be found in {channel}\n\n- Add Steam as a connection to your Discord account. (Found in settings, You can set it as hidden)\n- Head over to {url}\n- Login to your Discord account\n- Ask for help in the correct support channels or create a ticket with /create";
+const DEFAULT_TITLE: &str = "Verify your Discord account";
+
+/// Fills in a template's placeholders. Lines using `{channel}` or `{url}` are left out when the
+/// guild hasn't configured them.
+fn render(template: &str, channel: Option<u64>, url: Option<&str>, user: UserId) -> String {
+    template
+        .lines()
+        .filter(|line| channel.is_some() || !line.contains("{channel}"))
+        .filter(|line| url.is_some() || !line.contains("{url}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .replace(
+            "{channel}",
+            &channel
+                .map(|channel| format!("<#{}>", channel))
+                .unwrap_or_default(),
+        )
+        .replace("{url}", url.unwrap_or_default())
+        .replace("{user}", &format!("<@{}>", user))
+}
+
+/// Builds the verification instructions embed and its link button for a guild. `template`
+/// overrides the guild's `/verify` template, e.g. for onboarding DMs.
+pub fn verification_message(
+    settings: Option<&VerifySettings>,
+    template: Option<&str>,
+    user: UserId,
+) -> (CreateEmbed, Vec<CreateActionRow>) {
+    let settings = settings.cloned().unwrap_or_default();
+    let template = template
+        .or(settings.template.as_deref())
+        .unwrap_or(DEFAULT_TEMPLATE);
+
+    let embed = CreateEmbed::new()
+        .title(settings.title.as_deref().unwrap_or(DEFAULT_TITLE))
+        .description(render(
+            template,
+            settings.channel,
+            settings.url.as_deref(),
+            user,
+        ));
+
+    let components = match settings.url {
+        Some(url) => vec![CreateActionRow::Buttons(vec![
+            CreateButton::new_link(url).label("Verify")
+        ])],
+        None => vec![],
+    };
+
+    (embed, components)
+}
+
 #[derive(Debug)]
 pub struct VerifyCommand;
 
@@ -30,12 +88,59 @@ impl CommandInfo for VerifyCommand {
 impl Command for VerifyCommand {
     async fn execute<'a>(
         &self,
-        _handler: &BotEvents,
+        handler: &BotEvents,
         ctx: &Context,
         interaction: &'a mut CommandInteraction,
     ) -> Result<(), CommandExecutionError> {
-        let message = CreateInteractionResponseMessage::new()
-            .content("To gain access to support channels you first have to verify your Discord account.\nTo do this, please follow these steps (Also found in <#937373534651559966>)\n\n- Add Steam as a connection to your Discord account. (Found in settings, You can set it as hidden)\n- Head over to https://verify.lythium.dev/\n- Login to your Discord account\n- Ask for help in the correct support channels or create a ticket with /create");
+        let sub_cmd = match interaction.data.options.first() {
+            Some(sub_cmd) => sub_cmd,
+            None => {
+                return Err(Report::from(CommandExecutionError)
+                    .attach_printable("Failed to get sub command arg data"));
+            }
+        };
+
+        let options = match (sub_cmd.name.as_str(), &sub_cmd.value) {
+            ("steps", CommandDataOptionValue::SubCommand(options)) => options.clone(),
+            ("status", _) => return status(handler, ctx, interaction).await,
+            _ => {
+                return Err(Report::from(CommandExecutionError)
+                    .attach_printable("Invalid sub command type"));
+            }
+        };
+
+        let mut target = None;
+        let mut ephemeral = false;
+
+        for option in &options {
+            match (option.name.as_str(), &option.value) {
+                ("user", CommandDataOptionValue::User(user_id)) => target = Some(*user_id),
+                ("ephemeral", CommandDataOptionValue::Boolean(value)) => ephemeral = *value,
+                _ => {}
+            }
+        }
+
+        let settings = interaction
+            .guild_id
+            .and_then(|guild_id| handler.cfg.guild(guild_id.get()))
+            .map(|settings| &settings.verify);
+
+        let (embed, components) =
+            verification_message(settings, None, target.unwrap_or(interaction.user.id));
+
+        let mut message = CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .components(components);
+
+        match target {
+            // Ephemeral replies would never reach the user being pointed at the steps.
+            Some(target) if target != interaction.user.id => {
+                message = message
+                    .content(format!("<@{}>", target))
+                    .allowed_mentions(CreateAllowedMentions::new().users([target]));
+            }
+            _ => message = message.ephemeral(ephemeral),
+        }
 
         let builder = CreateInteractionResponse::Message(message);
 
@@ -50,6 +155,160 @@ impl Command for VerifyCommand {
     async fn register(&self, _: &BotEvents) -> CreateCommand {
         return CreateCommand::new(self.name())
             .description(self.description())
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "steps",
+                    "Show the verification steps",
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::User,
+                    "user",
+                    "Point another user at the verification steps",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "ephemeral",
+                    "Only show the steps to yourself",
+                )),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "status",
+                "Check whether your verification worked",
+            ))
             .dm_permission(false);
     }
 }
+
+fn describe_status(user: &User) -> CreateEmbed {
+    let linked = |value: Option<String>, missing: &str| match value {
+        Some(value) => format!("✅ `{}`", value),
+        None => format!("❌ {}", missing),
+    };
+
+    CreateEmbed::new()
+        .title("Verification status")
+        .field("Pulsar Link", linked(user.id.clone(), "Not linked"), false)
+        .field(
+            "Steam",
+            linked(
+                user.steam_id.map(|id| id.to_string()),
+                "Add Steam as a connection to your Discord account, then verify again",
+            ),
+            false,
+        )
+        .field(
+            "Gmodstore",
+            linked(
+                user.gmodstore_id.clone(),
+                "Log in with your Gmodstore account on the verification site",
+            ),
+            false,
+        )
+}
+
+/// `/verify status`, shows the invoking user what is linked and refreshes their roles.
+async fn status(
+    handler: &BotEvents,
+    ctx: &Context,
+    interaction: &mut CommandInteraction,
+) -> Result<(), CommandExecutionError> {
+    interaction
+        .defer_ephemeral(&ctx.http)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    let settings = interaction
+        .guild_id
+        .and_then(|guild_id| handler.cfg.guild(guild_id.get()));
+
+    let response = match handler.api.user_by_discord(interaction.user.id).await {
+        Ok(Some(user)) => {
+            let mut response = EditInteractionResponse::new().embed(describe_status(&user));
+
+            if let (Some(settings), Some(member)) = (settings, &interaction.member) {
+                let content = match role_sync::sync_member(
+                    &ctx.http,
+                    &handler.api,
+                    settings,
+                    member,
+                    false,
+                )
+                .await
+                {
+                    Ok(outcome) => outcome.describe(),
+                    Err(err) => {
+                        error!(
+                            "Failed to sync roles for {}: {:?}",
+                            interaction.user.id, err
+                        );
+
+                        String::from("Your roles couldn't be refreshed, please try again later.")
+                    }
+                };
+
+                response = response.content(content);
+            }
+
+            response
+        }
+        Ok(None) => {
+            let (embed, components) = verification_message(
+                settings.map(|settings| &settings.verify),
+                None,
+                interaction.user.id,
+            );
+
+            EditInteractionResponse::new()
+                .content("Your Discord account isn't linked to Pulsar Link yet.")
+                .embed(embed)
+                .components(components)
+        }
+        Err(err) => {
+            error!(
+                "Failed to look up {} in Pulsar Link: {:?}",
+                interaction.user.id, err
+            );
+
+            EditInteractionResponse::new()
+                .content("Unable to check your verification status. Please try again later.")
+        }
+    };
+
+    interaction
+        .edit_response(&ctx.http, response)
+        .await
+        .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_substitutes_placeholders() {
+        let rendered = render(
+            "Hey {user}, see {channel} and {url}",
+            Some(10),
+            Some("https://example.com"),
+            UserId::new(20),
+        );
+
+        assert_eq!(rendered, "Hey <@20>, see <#10> and https://example.com");
+    }
+
+    #[test]
+    fn render_leaves_out_unset_lines() {
+        let rendered = render(
+            "Hey {user}\nSee {channel}\nGo to {url}",
+            None,
+            None,
+            UserId::new(20),
+        );
+
+        assert_eq!(rendered, "Hey <@20>");
+    }
+}
@@ -0,0 +1,549 @@
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use error_stack::{Report, Result, ResultExt};
+use serenity::{
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::{
+        application::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+        guild::PartialGuild,
+        id::UserId,
+        Permissions,
+    },
+};
+use tracing::{error, warn};
+
+use crate::{
+    config::{EscalationAction, WarnEscalation},
+    database::{
+        cases::{Case, CaseAction, NewCase},
+        warnings::NewWarning,
+    },
+    duration,
+    event_handler::BotEvents,
+};
+
+use super::{
+    ban::{apply_ban, BanOptions},
+    moderation::{
+        describe_case, describe_notice, edit_reply, find_member, moderation_context, notice,
+        notify, record_case, refusal, reply_ephemeral, should_notify,
+    },
+    timeout::{apply_timeout, MAX_TIMEOUT},
+    Command, CommandExecutionError, CommandInfo,
+};
+
+/// Most warnings listed by `/warnings` before the rest are summarised.
+const LISTED_WARNINGS: usize = 20;
+
+/// An escalation rule with its duration parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escalation {
+    Timeout(Duration),
+    Ban(Option<Duration>),
+}
+
+impl Escalation {
+    /// `None` when the rule's duration is invalid, or missing from a timeout.
+    fn from_settings(escalation: &WarnEscalation) -> Option<Self> {
+        let duration = match &escalation.duration {
+            Some(input) => Some(duration::parse(input)?),
+            None => None,
+        };
+
+        match escalation.action {
+            EscalationAction::Timeout => Some(Escalation::Timeout(duration?.min(MAX_TIMEOUT))),
+            EscalationAction::Ban => Some(Escalation::Ban(duration)),
+        }
+    }
+
+    /// Applies the escalation on the bot's behalf, returning the case it was recorded as and, for
+    /// bans, any problem scheduling the unban.
+    async fn apply(
+        self,
+        handler: &BotEvents,
+        ctx: &Context,
+        guild: &PartialGuild,
+        target: UserId,
+        reason: &str,
+    ) -> Result<(Option<Case>, Option<&'static str>), CommandExecutionError> {
+        let bot_id = ctx.cache.current_user().id;
+
+        match self {
+            Escalation::Timeout(length) => {
+                apply_timeout(handler, ctx, guild.id, bot_id, target, length, Some(reason))
+                    .await
+                    .map(|case| (case, None))
+            }
+            Escalation::Ban(duration) => {
+                let options = BanOptions {
+                    reason: Some(reason),
+                    duration,
+                    ..Default::default()
+                };
+
+                apply_ban(
+                    handler,
+                    ctx,
+                    guild.id,
+                    bot_id,
+                    target,
+                    &options,
+                    Some(reason),
+                )
+                .await
+                .map(|applied| (applied.case, applied.schedule_problem))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Escalation {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Escalation::Timeout(length) => {
+                write!(fmt, "timed out for {}", duration::format(*length))
+            }
+            Escalation::Ban(Some(length)) => {
+                write!(fmt, "banned for {}", duration::format(*length))
+            }
+            Escalation::Ban(None) => fmt.write_str("banned"),
+        }
+    }
+}
+
+fn user_arg(interaction: &CommandInteraction) -> Result<UserId, CommandExecutionError> {
+    interaction
+        .data
+        .options
+        .iter()
+        .find_map(|option| match (option.name.as_str(), &option.value) {
+            ("user", CommandDataOptionValue::User(user_id)) => Some(*user_id),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            Report::from(CommandExecutionError).attach_printable("Failed to get user arg")
+        })
+}
+
+#[derive(Debug)]
+pub struct WarnCommand;
+
+impl CommandInfo for WarnCommand {
+    fn name(&self) -> String {
+        String::from("warn")
+    }
+
+    fn description(&self) -> String {
+        String::from("Warn a user, escalating once they reach the server's limits")
+    }
+}
+
+#[async_trait]
+impl Command for WarnCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let target = user_arg(interaction)?;
+        let mut reason = None;
+        let mut notify_arg = None;
+
+        for option in &interaction.data.options {
+            match (option.name.as_str(), &option.value) {
+                ("reason", CommandDataOptionValue::String(warn_reason)) => {
+                    reason = Some(warn_reason.as_str())
+                }
+                ("notify", CommandDataOptionValue::Boolean(notify)) => notify_arg = Some(*notify),
+                _ => {}
+            }
+        }
+
+        let Some(reason) = reason else {
+            return Err(
+                Report::from(CommandExecutionError).attach_printable("Failed to get reason arg")
+            );
+        };
+
+        let (guild, interaction_member) = moderation_context(ctx, interaction).await?;
+
+        let Some(target_member) = find_member(ctx, guild.id, target).await? else {
+            return reply_ephemeral(
+                ctx,
+                interaction,
+                format!("<@{}> isn't in this server", target),
+            )
+            .await;
+        };
+
+        if let Some(refusal) = refusal(
+            &guild,
+            interaction_member,
+            target,
+            Some(&target_member),
+            "warn",
+        ) {
+            return reply_ephemeral(ctx, interaction, refusal).await;
+        }
+
+        interaction
+            .defer_ephemeral(&ctx.http)
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))?;
+
+        let case = record_case(
+            handler,
+            ctx,
+            NewCase {
+                guild_id: guild.id.get(),
+                action: CaseAction::Warn,
+                target_id: target.get(),
+                moderator_id: interaction.user.id.get(),
+                reason: Some(reason.to_string()),
+                duration: None,
+            },
+        )
+        .await;
+
+        handler
+            .db
+            .add_warning(NewWarning {
+                guild_id: guild.id.get(),
+                user_id: target.get(),
+                moderator_id: interaction.user.id.get(),
+                reason: reason.to_string(),
+                case_id: case.as_ref().map(|case| case.id),
+            })
+            .change_context(CommandExecutionError)?;
+
+        let count = handler
+            .db
+            .warnings_for(guild.id.get(), target.get())
+            .change_context(CommandExecutionError)?
+            .len() as u32;
+
+        let rule = handler
+            .cfg
+            .guild(guild.id.get())
+            .and_then(|settings| settings.escalation(count));
+        let escalation = rule.and_then(|rule| {
+            let escalation = Escalation::from_settings(rule);
+
+            if escalation.is_none() {
+                warn!(
+                    "Invalid warn-escalation for {} warnings in guild {}",
+                    count, guild.id
+                );
+            }
+
+            escalation
+        });
+
+        // Sent before escalating, a ban would leave no shared server to DM them through
+        let notified = if should_notify(handler, guild.id, notify_arg) {
+            let mut notice = notice(
+                format!("You have been warned in {}", guild.name),
+                Some(reason),
+            )
+            .field("Warnings", count.to_string(), true);
+
+            if let Some(escalation) = escalation {
+                notice = notice.field("Action", format!("You have been {}", escalation), true);
+            }
+
+            Some(notify(ctx, target, notice).await)
+        } else {
+            None
+        };
+
+        let user = &target_member.user;
+        let mut content = format!(
+            "Warned {}{}, they now have {} warning{}",
+            user.global_name.as_deref().unwrap_or(&user.name),
+            describe_case(case.as_ref()),
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+
+        match (rule, escalation) {
+            (Some(_), Some(escalation)) => {
+                let escalation_reason = format!("Reached {} warnings: {}", count, reason);
+
+                match escalation
+                    .apply(handler, ctx, &guild, target, &escalation_reason)
+                    .await
+                {
+                    Ok((escalation_case, schedule_problem)) => {
+                        content.push_str(&format!(
+                            "\nThey were automatically {}{}",
+                            escalation,
+                            describe_case(escalation_case.as_ref())
+                        ));
+
+                        if let Some(problem) = schedule_problem {
+                            content.push_str(&format!("\n⚠️ {}", problem));
+                        }
+                    }
+                    Err(err) => {
+                        error!("Failed to escalate warning for {}: {:?}", target, err);
+                        content.push_str(&format!(
+                            "\n❌ Failed to escalate, they should have been {}",
+                            escalation
+                        ));
+                    }
+                }
+            }
+            (Some(_), None) => content.push_str(&format!(
+                "\nThe escalation for {} warnings is misconfigured, so nothing else was done",
+                count
+            )),
+            _ => {}
+        }
+
+        content.push_str(&describe_notice(notified, "warning"));
+
+        edit_reply(ctx, interaction, content).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::User, "user", "The user to warn")
+                    .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "reason",
+                    "Reason for the warning",
+                )
+                .required(true),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "notify",
+                "DM the user about the warning, defaults to the server's setting",
+            ))
+            .default_member_permissions(Permissions::MODERATE_MEMBERS)
+            .dm_permission(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct WarningsCommand;
+
+impl CommandInfo for WarningsCommand {
+    fn name(&self) -> String {
+        String::from("warnings")
+    }
+
+    fn description(&self) -> String {
+        String::from("List a user's warnings")
+    }
+}
+
+#[async_trait]
+impl Command for WarningsCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Command used outside a guild"));
+        };
+
+        let target = user_arg(interaction)?;
+
+        let warnings = handler
+            .db
+            .warnings_for(guild_id.get(), target.get())
+            .change_context(CommandExecutionError)?;
+
+        if warnings.is_empty() {
+            return reply_ephemeral(ctx, interaction, format!("<@{}> has no warnings", target))
+                .await;
+        }
+
+        let mut description = warnings
+            .iter()
+            .take(LISTED_WARNINGS)
+            .map(|warning| {
+                format!(
+                    "**#{}** <t:{}:d> by <@{}>: {}",
+                    warning.id, warning.created_at, warning.moderator_id, warning.reason
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if warnings.len() > LISTED_WARNINGS {
+            description.push_str(&format!(
+                "\n...and {} older",
+                warnings.len() - LISTED_WARNINGS
+            ));
+        }
+
+        let embed = CreateEmbed::new()
+            .title(format!("{} warnings", warnings.len()))
+            .description(format!("<@{}>\n\n{}", target, description));
+
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .embed(embed)
+                        .ephemeral(true),
+                ),
+            )
+            .await
+            .map_err(|e| Report::from(e).change_context(CommandExecutionError))
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::User,
+                    "user",
+                    "The user to list warnings for",
+                )
+                .required(true),
+            )
+            .default_member_permissions(Permissions::MODERATE_MEMBERS)
+            .dm_permission(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct UnwarnCommand;
+
+impl CommandInfo for UnwarnCommand {
+    fn name(&self) -> String {
+        String::from("unwarn")
+    }
+
+    fn description(&self) -> String {
+        String::from("Remove one or all of a user's warnings")
+    }
+}
+
+#[async_trait]
+impl Command for UnwarnCommand {
+    async fn execute<'a>(
+        &self,
+        handler: &BotEvents,
+        ctx: &Context,
+        interaction: &'a mut CommandInteraction,
+    ) -> Result<(), CommandExecutionError> {
+        let Some(guild_id) = interaction.guild_id else {
+            return Err(Report::from(CommandExecutionError)
+                .attach_printable("Command used outside a guild"));
+        };
+
+        let target = user_arg(interaction)?;
+        let id = interaction.data.options.iter().find_map(|option| {
+            match (option.name.as_str(), &option.value) {
+                ("id", CommandDataOptionValue::Integer(id)) => Some(*id),
+                _ => None,
+            }
+        });
+
+        let content = match id {
+            Some(id) => match handler
+                .db
+                .remove_warning(guild_id.get(), target.get(), id)
+                .change_context(CommandExecutionError)?
+            {
+                Some(warning) => format!(
+                    "Removed warning #{} from <@{}>: {}",
+                    warning.id, target, warning.reason
+                ),
+                None => format!("<@{}> has no warning #{}", target, id),
+            },
+            None => match handler
+                .db
+                .clear_warnings(guild_id.get(), target.get())
+                .change_context(CommandExecutionError)?
+            {
+                0 => format!("<@{}> has no warnings", target),
+                cleared => format!("Cleared {} warnings from <@{}>", cleared, target),
+            },
+        };
+
+        reply_ephemeral(ctx, interaction, content).await
+    }
+
+    async fn register(&self, _: &BotEvents) -> CreateCommand {
+        CreateCommand::new(self.name())
+            .description(self.description())
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::User,
+                    "user",
+                    "The user to remove warnings from",
+                )
+                .required(true),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::Integer,
+                    "id",
+                    "The warning to remove, clears every warning when left out",
+                )
+                .min_int_value(1),
+            )
+            .default_member_permissions(Permissions::MODERATE_MEMBERS)
+            .dm_permission(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(action: EscalationAction, duration: Option<&str>) -> WarnEscalation {
+        WarnEscalation {
+            warnings: 3,
+            action,
+            duration: duration.map(String::from),
+        }
+    }
+
+    #[test]
+    fn parses_escalations() {
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        assert_eq!(
+            Escalation::from_settings(&rule(EscalationAction::Timeout, Some("24h"))),
+            Some(Escalation::Timeout(day))
+        );
+        assert_eq!(
+            Escalation::from_settings(&rule(EscalationAction::Timeout, Some("60d"))),
+            Some(Escalation::Timeout(MAX_TIMEOUT))
+        );
+        assert_eq!(
+            Escalation::from_settings(&rule(EscalationAction::Timeout, None)),
+            None
+        );
+        assert_eq!(
+            Escalation::from_settings(&rule(EscalationAction::Ban, None)),
+            Some(Escalation::Ban(None))
+        );
+        assert_eq!(
+            Escalation::from_settings(&rule(EscalationAction::Ban, Some("soon"))),
+            None
+        );
+    }
+}
@@ -1,9 +1,10 @@
-use std::{fmt, fs};
+use std::{collections::HashMap, fmt, fs};
 
 use error_stack::{Context, Report, Result};
 use serde::{Deserialize, Serialize};
+use serenity::model::id::{ChannelId, GuildId, RoleId};
 
-static CONFIG_FILE_NAME: &'static str = "config.toml";
+static CONFIG_FILE_NAME: &str = "config.toml";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ConfigFile {
@@ -14,6 +15,241 @@ pub struct ConfigFile {
     pub api_url: String,
     #[serde(rename = "api-key")]
     pub api_key: String,
+    /// Directory the bot keeps its own state in.
+    #[serde(rename = "data-dir", default = "default_data_dir")]
+    pub data_dir: String,
+    /// Per-guild settings, keyed by guild ID.
+    #[serde(rename = "guild-settings", default)]
+    pub guild_settings: HashMap<String, GuildSettings>,
+    /// Periodic role reconciliation, disabled when absent.
+    #[serde(rename = "role-reconcile")]
+    pub role_reconcile: Option<RoleReconcileSettings>,
+    /// Whether bans and unbans apply to every configured guild unless told otherwise.
+    #[serde(rename = "ban-sync", default)]
+    pub ban_sync: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct GuildSettings {
+    /// Role given to every member with a Pulsar Link account.
+    #[serde(rename = "verified-role")]
+    pub verified_role: Option<u64>,
+    /// Roles given to owners of an addon, keyed by Gmodstore addon ID.
+    #[serde(rename = "addon-roles", default)]
+    pub addon_roles: HashMap<String, u64>,
+    /// Channel the bot posts job reports to.
+    #[serde(rename = "log-channel")]
+    pub log_channel: Option<u64>,
+    /// Channel moderation cases are posted to, defaults to the log channel.
+    #[serde(rename = "mod-log-channel")]
+    pub mod_log_channel: Option<u64>,
+    /// Messages unlinked members when they join, disabled when absent.
+    pub onboarding: Option<OnboardingSettings>,
+    #[serde(default)]
+    pub verify: VerifySettings,
+    /// Roles allowed to post lookups publicly.
+    #[serde(rename = "staff-roles", default)]
+    pub staff_roles: Vec<u64>,
+    /// Default visibility of `/user` and `/addons` replies.
+    #[serde(rename = "lookup-visibility", default)]
+    pub lookup_visibility: Visibility,
+    /// How much of a user's message history `/ban` deletes when no window is picked.
+    #[serde(rename = "ban-delete-messages", default)]
+    pub ban_delete_messages: DeleteMessages,
+    #[serde(rename = "ban-notify", default)]
+    pub ban_notify: BanNotifySettings,
+    /// Keeps bans and unbans made in other guilds from being applied here.
+    #[serde(rename = "ban-sync-opt-out", default)]
+    pub ban_sync_opt_out: bool,
+    /// Actions `/warn` takes once a user reaches a number of warnings.
+    #[serde(rename = "warn-escalation", default)]
+    pub warn_escalation: Vec<WarnEscalation>,
+    #[serde(default)]
+    pub lockdown: LockdownSettings,
+    /// Alerts moderators to bursts of joins, disabled when absent.
+    #[serde(rename = "raid-detection")]
+    pub raid_detection: Option<RaidDetectionSettings>,
+}
+
+impl GuildSettings {
+    pub fn mod_log_channel(&self) -> Option<ChannelId> {
+        self.mod_log_channel
+            .or(self.log_channel)
+            .map(ChannelId::new)
+    }
+
+    /// The escalation triggered by reaching exactly `warnings` warnings.
+    pub fn escalation(&self, warnings: u32) -> Option<&WarnEscalation> {
+        self.warn_escalation
+            .iter()
+            .find(|escalation| escalation.warnings == warnings)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Ephemeral,
+    Public,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteMessages {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "1h")]
+    Hour,
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+}
+
+impl DeleteMessages {
+    /// Parses the values used in the config and the `delete_messages` command option.
+    pub fn parse(window: &str) -> Option<Self> {
+        match window {
+            "none" => Some(DeleteMessages::None),
+            "1h" => Some(DeleteMessages::Hour),
+            "24h" => Some(DeleteMessages::Day),
+            "7d" => Some(DeleteMessages::Week),
+            _ => None,
+        }
+    }
+
+    pub fn seconds(self) -> u32 {
+        match self {
+            DeleteMessages::None => 0,
+            DeleteMessages::Hour => 60 * 60,
+            DeleteMessages::Day => 24 * 60 * 60,
+            DeleteMessages::Week => 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BanNotifySettings {
+    /// Whether `/ban`, `/kick`, `/timeout` and `/warn` DM the user first unless told otherwise.
+    /// The appeal is only included in ban notices.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Appeal link or instructions included in the DM.
+    pub appeal: Option<String>,
+    /// Staff channel appeals are sent to. When set, ban notices get a button to appeal with.
+    #[serde(rename = "appeal-channel")]
+    pub appeal_channel: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WarnEscalation {
+    pub warnings: u32,
+    pub action: EscalationAction,
+    /// How long the timeout or ban lasts, such as `24h`. Bans without one are permanent.
+    pub duration: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EscalationAction {
+    Timeout,
+    Ban,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct LockdownSettings {
+    /// Channels locked together during a lockdown.
+    #[serde(default)]
+    pub channels: Vec<u64>,
+    /// Role that loses send permissions, defaults to @everyone.
+    pub role: Option<u64>,
+}
+
+impl LockdownSettings {
+    pub fn role(&self, guild_id: GuildId) -> RoleId {
+        // The @everyone role shares the guild's ID
+        RoleId::new(self.role.unwrap_or(guild_id.get()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RaidDetectionSettings {
+    /// Joins within the window that count as a raid.
+    pub joins: usize,
+    #[serde(rename = "window-seconds", default = "default_raid_window")]
+    pub window_seconds: u64,
+    /// Accounts younger than this, such as `7d`, count as new.
+    #[serde(rename = "new-account-age", default = "default_new_account_age")]
+    pub new_account_age: String,
+    /// Share of the joins, from 0 to 1, that also have to be new accounts. When set, only the
+    /// new accounts are flagged.
+    #[serde(rename = "new-account-ratio", default)]
+    pub new_account_ratio: f64,
+    /// Channel alerts are posted to, defaults to the mod log channel.
+    #[serde(rename = "alert-channel")]
+    pub alert_channel: Option<u64>,
+    /// Times out flagged accounts for this long, such as `1h`.
+    #[serde(rename = "auto-timeout")]
+    pub auto_timeout: Option<String>,
+    /// Locks the guild's lockdown channels when a raid is detected.
+    #[serde(rename = "auto-lockdown", default)]
+    pub auto_lockdown: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct VerifySettings {
+    /// Instructions shown by `/verify steps`, supporting `{channel}`, `{url}` and `{user}`
+    /// placeholders.
+    pub template: Option<String>,
+    pub title: Option<String>,
+    /// Channel the verification steps are pinned in, substituted for `{channel}`. Template lines
+    /// using it are left out when unset.
+    pub channel: Option<u64>,
+    /// Verification site, substituted for `{url}` and linked from the button. Template lines
+    /// using it, and the button, are left out when unset.
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct OnboardingSettings {
+    /// Template for the DM sent to unlinked members, defaults to the `/verify steps` template.
+    pub message: Option<String>,
+    /// Channel to mention the member in when their DMs are closed.
+    #[serde(rename = "fallback-channel")]
+    pub fallback_channel: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleReconcileSettings {
+    #[serde(rename = "interval-minutes", default = "default_reconcile_interval")]
+    pub interval_minutes: u64,
+    /// Upper bound on members checked per second, each check costs up to two backend requests.
+    #[serde(rename = "members-per-second", default = "default_reconcile_rate")]
+    pub members_per_second: u32,
+    /// Report the changes that would be made without touching any roles.
+    #[serde(rename = "dry-run", default)]
+    pub dry_run: bool,
+}
+
+fn default_data_dir() -> String {
+    String::from(".")
+}
+
+fn default_reconcile_interval() -> u64 {
+    360
+}
+
+fn default_reconcile_rate() -> u32 {
+    2
+}
+
+fn default_raid_window() -> u64 {
+    60
+}
+
+fn default_new_account_age() -> String {
+    String::from("7d")
 }
 
 #[derive(Debug)]
@@ -28,6 +264,10 @@ impl fmt::Display for ConfigFileError {
 impl Context for ConfigFileError {}
 
 impl ConfigFile {
+    pub fn guild(&self, guild_id: u64) -> Option<&GuildSettings> {
+        self.guild_settings.get(&guild_id.to_string())
+    }
+
     pub fn read() -> Result<ConfigFile, ConfigFileError> {
         let config_file = fs::read(format!("./{}", CONFIG_FILE_NAME)).map_err(|e| {
             Report::from(e)
@@ -0,0 +1,235 @@
+use error_stack::{Report, Result};
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::duration::unix_now;
+
+use super::{Database, DatabaseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppealStatus {
+    Pending,
+    Accepted,
+    Denied,
+}
+
+impl AppealStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            AppealStatus::Pending => "pending",
+            AppealStatus::Accepted => "accepted",
+            AppealStatus::Denied => "denied",
+        }
+    }
+
+    fn from_str(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(AppealStatus::Pending),
+            "accepted" => Some(AppealStatus::Accepted),
+            "denied" => Some(AppealStatus::Denied),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Appeal {
+    pub id: i64,
+    pub guild_id: u64,
+    pub user_id: u64,
+    /// The ban case being appealed, if one was recorded.
+    pub case_id: Option<i64>,
+    pub message: String,
+    pub status: AppealStatus,
+    pub reviewer_id: Option<u64>,
+    /// Staff note left when reviewing.
+    pub note: Option<String>,
+    /// Unix timestamp, in seconds.
+    pub created_at: u64,
+    pub reviewed_at: Option<u64>,
+}
+
+impl Appeal {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let status: String = row.get("status")?;
+
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get::<_, i64>("guild_id")? as u64,
+            user_id: row.get::<_, i64>("user_id")? as u64,
+            case_id: row.get("case_id")?,
+            message: row.get("message")?,
+            status: AppealStatus::from_str(&status).ok_or_else(|| {
+                rusqlite::Error::InvalidColumnType(
+                    0,
+                    String::from("status"),
+                    rusqlite::types::Type::Text,
+                )
+            })?,
+            reviewer_id: row
+                .get::<_, Option<i64>>("reviewer_id")?
+                .map(|id| id as u64),
+            note: row.get("note")?,
+            created_at: row.get::<_, i64>("created_at")? as u64,
+            reviewed_at: row
+                .get::<_, Option<i64>>("reviewed_at")?
+                .map(|at| at as u64),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewAppeal {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub case_id: Option<i64>,
+    pub message: String,
+}
+
+impl Database {
+    pub fn create_appeal(&self, appeal: NewAppeal) -> Result<Appeal, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "INSERT INTO appeals (guild_id, user_id, case_id, message, status, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             RETURNING *",
+            params![
+                appeal.guild_id as i64,
+                appeal.user_id as i64,
+                appeal.case_id,
+                appeal.message,
+                AppealStatus::Pending.as_str(),
+                unix_now() as i64,
+            ],
+            Appeal::from_row,
+        )
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Forgets an appeal that never reached the staff, so the user can send it again.
+    pub fn delete_appeal(&self, id: i64) -> Result<(), DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute("DELETE FROM appeals WHERE id = ?1", params![id])
+            .map(|_| ())
+            .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    pub fn appeal(&self, id: i64) -> Result<Option<Appeal>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "SELECT * FROM appeals WHERE id = ?1",
+            params![id],
+            Appeal::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// The user's appeal in a guild that's still waiting for a review, if they have one.
+    pub fn pending_appeal(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> Result<Option<Appeal>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "SELECT * FROM appeals WHERE guild_id = ?1 AND user_id = ?2 AND status = ?3",
+            params![
+                guild_id as i64,
+                user_id as i64,
+                AppealStatus::Pending.as_str()
+            ],
+            Appeal::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Accepts or denies a pending appeal, returning it unless it doesn't exist or was already
+    /// reviewed.
+    pub fn review_appeal(
+        &self,
+        id: i64,
+        status: AppealStatus,
+        reviewer_id: u64,
+        note: Option<&str>,
+    ) -> Result<Option<Appeal>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "UPDATE appeals SET status = ?2, reviewer_id = ?3, note = ?4, reviewed_at = ?5
+             WHERE id = ?1 AND status = ?6
+             RETURNING *",
+            params![
+                id,
+                status.as_str(),
+                reviewer_id as i64,
+                note,
+                unix_now() as i64,
+                AppealStatus::Pending.as_str(),
+            ],
+            Appeal::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reviews_appeals_once() {
+        let db = Database::in_memory();
+
+        let appeal = db
+            .create_appeal(NewAppeal {
+                guild_id: 1,
+                user_id: 10,
+                case_id: Some(3),
+                message: String::from("I'm sorry"),
+            })
+            .unwrap();
+
+        assert_eq!(appeal.status, AppealStatus::Pending);
+        assert_eq!(db.appeal(appeal.id).unwrap(), Some(appeal.clone()));
+        assert_eq!(db.pending_appeal(1, 10).unwrap(), Some(appeal.clone()));
+        assert_eq!(db.pending_appeal(2, 10).unwrap(), None);
+
+        let denied = db
+            .review_appeal(appeal.id, AppealStatus::Denied, 2, Some("Ban evasion"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(denied.status, AppealStatus::Denied);
+        assert_eq!(denied.reviewer_id, Some(2));
+        assert_eq!(denied.note.as_deref(), Some("Ban evasion"));
+
+        assert_eq!(
+            db.review_appeal(appeal.id, AppealStatus::Accepted, 2, None)
+                .unwrap(),
+            None
+        );
+        assert_eq!(db.pending_appeal(1, 10).unwrap(), None);
+    }
+
+    #[test]
+    fn forgets_unsent_appeals() {
+        let db = Database::in_memory();
+
+        let appeal = db
+            .create_appeal(NewAppeal {
+                guild_id: 1,
+                user_id: 10,
+                case_id: None,
+                message: String::from("I'm sorry"),
+            })
+            .unwrap();
+
+        db.delete_appeal(appeal.id).unwrap();
+        assert_eq!(db.pending_appeal(1, 10).unwrap(), None);
+    }
+}
@@ -0,0 +1,228 @@
+use std::{fmt, time::Duration};
+
+use error_stack::{Report, Result};
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::duration::unix_now;
+
+use super::{Database, DatabaseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseAction {
+    Ban,
+    Unban,
+    Kick,
+    Timeout,
+    Warn,
+}
+
+impl CaseAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            CaseAction::Ban => "ban",
+            CaseAction::Unban => "unban",
+            CaseAction::Kick => "kick",
+            CaseAction::Timeout => "timeout",
+            CaseAction::Warn => "warn",
+        }
+    }
+
+    fn from_str(action: &str) -> Option<Self> {
+        match action {
+            "ban" => Some(CaseAction::Ban),
+            "unban" => Some(CaseAction::Unban),
+            "kick" => Some(CaseAction::Kick),
+            "timeout" => Some(CaseAction::Timeout),
+            "warn" => Some(CaseAction::Warn),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CaseAction {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(match self {
+            CaseAction::Ban => "Ban",
+            CaseAction::Unban => "Unban",
+            CaseAction::Kick => "Kick",
+            CaseAction::Timeout => "Timeout",
+            CaseAction::Warn => "Warning",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// Numbered per guild, starting at 1.
+    pub id: i64,
+    pub guild_id: u64,
+    pub action: CaseAction,
+    pub target_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    pub duration: Option<Duration>,
+    /// Unix timestamp, in seconds.
+    pub created_at: u64,
+}
+
+impl Case {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let action: String = row.get("action")?;
+
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get::<_, i64>("guild_id")? as u64,
+            action: CaseAction::from_str(&action).ok_or_else(|| {
+                rusqlite::Error::InvalidColumnType(
+                    0,
+                    String::from("action"),
+                    rusqlite::types::Type::Text,
+                )
+            })?,
+            target_id: row.get::<_, i64>("target_id")? as u64,
+            moderator_id: row.get::<_, i64>("moderator_id")? as u64,
+            reason: row.get("reason")?,
+            duration: row
+                .get::<_, Option<i64>>("duration_secs")?
+                .map(|secs| Duration::from_secs(secs as u64)),
+            created_at: row.get::<_, i64>("created_at")? as u64,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewCase {
+    pub guild_id: u64,
+    pub action: CaseAction,
+    pub target_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+impl Database {
+    pub fn create_case(&self, case: NewCase) -> Result<Case, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "INSERT INTO cases (guild_id, id, action, target_id, moderator_id, reason, duration_secs, created_at)
+             VALUES (?1, (SELECT COALESCE(MAX(id), 0) + 1 FROM cases WHERE guild_id = ?1), ?2, ?3, ?4, ?5, ?6, ?7)
+             RETURNING *",
+            params![
+                case.guild_id as i64,
+                case.action.as_str(),
+                case.target_id as i64,
+                case.moderator_id as i64,
+                case.reason,
+                case.duration.map(|duration| duration.as_secs() as i64),
+                unix_now() as i64,
+            ],
+            Case::from_row,
+        )
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    pub fn case(&self, guild_id: u64, id: i64) -> Result<Option<Case>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "SELECT * FROM cases WHERE guild_id = ?1 AND id = ?2",
+            params![guild_id as i64, id],
+            Case::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Every case against a user in a guild, newest first.
+    pub fn cases_for(&self, guild_id: u64, target_id: u64) -> Result<Vec<Case>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare("SELECT * FROM cases WHERE guild_id = ?1 AND target_id = ?2 ORDER BY id DESC")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![guild_id as i64, target_id as i64], Case::from_row)?
+                    .collect()
+            })
+            .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Removes a case opened ahead of an action that didn't go through.
+    pub fn delete_case(&self, guild_id: u64, id: i64) -> Result<(), DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "DELETE FROM cases WHERE guild_id = ?1 AND id = ?2",
+            params![guild_id as i64, id],
+        )
+        .map(|_| ())
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Replaces a case's reason, returning the updated case if it exists.
+    pub fn update_case_reason(
+        &self,
+        guild_id: u64,
+        id: i64,
+        reason: &str,
+    ) -> Result<Option<Case>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "UPDATE cases SET reason = ?3 WHERE guild_id = ?1 AND id = ?2 RETURNING *",
+            params![guild_id as i64, id, reason],
+            Case::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_case(target_id: u64, action: CaseAction) -> NewCase {
+        NewCase {
+            guild_id: 1,
+            action,
+            target_id,
+            moderator_id: 2,
+            reason: Some(String::from("spam")),
+            duration: Some(Duration::from_secs(60)),
+        }
+    }
+
+    #[test]
+    fn records_and_edits_cases() {
+        let db = Database::in_memory();
+
+        let first = db.create_case(new_case(10, CaseAction::Ban)).unwrap();
+        let second = db.create_case(new_case(10, CaseAction::Unban)).unwrap();
+        db.create_case(new_case(11, CaseAction::Warn)).unwrap();
+
+        let other_guild = db
+            .create_case(NewCase {
+                guild_id: 2,
+                ..new_case(10, CaseAction::Kick)
+            })
+            .unwrap();
+
+        assert_eq!((first.id, second.id, other_guild.id), (1, 2, 1));
+        assert_eq!(first.duration, Some(Duration::from_secs(60)));
+        assert_eq!(db.case(1, first.id).unwrap(), Some(first.clone()));
+        assert_eq!(db.case(2, first.id).unwrap(), Some(other_guild));
+        assert_eq!(db.case(2, second.id).unwrap(), None);
+        assert_eq!(
+            db.cases_for(1, 10).unwrap(),
+            vec![second.clone(), first.clone()]
+        );
+
+        let edited = db.update_case_reason(1, first.id, "raid").unwrap().unwrap();
+        assert_eq!(edited.reason.as_deref(), Some("raid"));
+        assert_eq!(db.update_case_reason(1, 999, "raid").unwrap(), None);
+
+        db.delete_case(1, second.id).unwrap();
+        assert_eq!(db.case(1, second.id).unwrap(), None);
+    }
+}
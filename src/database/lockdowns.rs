@@ -0,0 +1,109 @@
+use error_stack::{Report, Result};
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::{Database, DatabaseError};
+
+/// A channel locked for a role, with what to restore once it's unlocked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockdown {
+    pub channel_id: u64,
+    pub role_id: u64,
+    /// The role's overwrite in the channel before it was locked, as allow and deny bits. `None`
+    /// when the role had no overwrite there.
+    pub previous: Option<(u64, u64)>,
+}
+
+impl Lockdown {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let allow: Option<i64> = row.get("previous_allow")?;
+        let deny: Option<i64> = row.get("previous_deny")?;
+
+        Ok(Self {
+            channel_id: row.get::<_, i64>("channel_id")? as u64,
+            role_id: row.get::<_, i64>("role_id")? as u64,
+            previous: allow
+                .zip(deny)
+                .map(|(allow, deny)| (allow as u64, deny as u64)),
+        })
+    }
+}
+
+impl Database {
+    /// Records a channel being locked, replacing any stale record for the same channel and role.
+    pub fn record_lockdown(&self, lockdown: &Lockdown) -> Result<(), DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO lockdowns (channel_id, role_id, previous_allow, previous_deny)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                lockdown.channel_id as i64,
+                lockdown.role_id as i64,
+                lockdown.previous.map(|(allow, _)| allow as i64),
+                lockdown.previous.map(|(_, deny)| deny as i64),
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    pub fn lockdown(
+        &self,
+        channel_id: u64,
+        role_id: u64,
+    ) -> Result<Option<Lockdown>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "SELECT * FROM lockdowns WHERE channel_id = ?1 AND role_id = ?2",
+            params![channel_id as i64, role_id as i64],
+            Lockdown::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Forgets a channel's lockdown once it's unlocked, returning whether there was one.
+    pub fn remove_lockdown(&self, channel_id: u64, role_id: u64) -> Result<bool, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "DELETE FROM lockdowns WHERE channel_id = ?1 AND role_id = ?2",
+            params![channel_id as i64, role_id as i64],
+        )
+        .map(|removed| removed > 0)
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_overwrite_to_restore() {
+        let db = Database::in_memory();
+
+        let with_overwrite = Lockdown {
+            channel_id: 1,
+            role_id: 2,
+            previous: Some((2048, 0)),
+        };
+        let without_overwrite = Lockdown {
+            channel_id: 3,
+            role_id: 2,
+            previous: None,
+        };
+
+        db.record_lockdown(&with_overwrite).unwrap();
+        db.record_lockdown(&without_overwrite).unwrap();
+
+        assert_eq!(db.lockdown(1, 2).unwrap(), Some(with_overwrite));
+        assert_eq!(db.lockdown(3, 2).unwrap(), Some(without_overwrite));
+        assert_eq!(db.lockdown(1, 4).unwrap(), None);
+
+        assert!(db.remove_lockdown(1, 2).unwrap());
+        assert!(!db.remove_lockdown(1, 2).unwrap());
+        assert_eq!(db.lockdown(1, 2).unwrap(), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::{fmt, path::PathBuf, sync::Mutex};
+
+use error_stack::{Context, Report, Result};
+use rusqlite::Connection;
+
+pub mod appeals;
+pub mod cases;
+pub mod lockdowns;
+pub mod tempbans;
+pub mod warnings;
+
+static DATABASE_FILE_NAME: &str = "pulsar-link-bot.sqlite3";
+
+/// Tables are only ever added, so creating whichever are missing brings any older database up to
+/// date.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cases (
+    guild_id INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    target_id INTEGER NOT NULL,
+    moderator_id INTEGER NOT NULL,
+    reason TEXT,
+    duration_secs INTEGER,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, id)
+);
+
+CREATE INDEX IF NOT EXISTS cases_target ON cases (guild_id, target_id);
+
+CREATE TABLE IF NOT EXISTS warnings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    moderator_id INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    case_id INTEGER,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS warnings_user ON warnings (guild_id, user_id);
+
+CREATE TABLE IF NOT EXISTS appeals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    guild_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    case_id INTEGER,
+    message TEXT NOT NULL,
+    status TEXT NOT NULL,
+    reviewer_id INTEGER,
+    note TEXT,
+    created_at INTEGER NOT NULL,
+    reviewed_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS appeals_user ON appeals (guild_id, user_id);
+
+CREATE TABLE IF NOT EXISTS tempbans (
+    guild_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    moderator_id INTEGER NOT NULL,
+    reason TEXT,
+    unban_at INTEGER NOT NULL,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS lockdowns (
+    channel_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+    previous_allow INTEGER,
+    previous_deny INTEGER,
+    PRIMARY KEY (channel_id, role_id)
+);
+";
+
+#[derive(Debug)]
+pub struct DatabaseError;
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Error accessing the database")
+    }
+}
+
+impl Context for DatabaseError {}
+
+/// The bot's own SQLite database, kept in the configured data directory.
+pub struct Database {
+    conn: Mutex<Connection>,
+}
+
+impl Database {
+    pub fn open(data_dir: &str) -> Result<Self, DatabaseError> {
+        let path = PathBuf::from(data_dir).join(DATABASE_FILE_NAME);
+
+        let conn = Connection::open(&path).map_err(|e| {
+            Report::from(e)
+                .attach_printable(format!("Failed to open {}", path.display()))
+                .change_context(DatabaseError)
+        })?;
+
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, DatabaseError> {
+        conn.execute_batch(SCHEMA).map_err(|e| {
+            Report::from(e)
+                .attach_printable("Failed to create tables")
+                .change_context(DatabaseError)
+        })?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+}
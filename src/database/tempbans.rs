@@ -0,0 +1,127 @@
+use error_stack::{Report, Result};
+use rusqlite::{params, OptionalExtension, Row};
+
+use super::{Database, DatabaseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TempBan {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub moderator_id: u64,
+    pub reason: Option<String>,
+    /// Unix timestamp, in seconds, the ban should be lifted at.
+    pub unban_at: u64,
+}
+
+impl TempBan {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            guild_id: row.get::<_, i64>("guild_id")? as u64,
+            user_id: row.get::<_, i64>("user_id")? as u64,
+            moderator_id: row.get::<_, i64>("moderator_id")? as u64,
+            reason: row.get("reason")?,
+            unban_at: row.get::<_, i64>("unban_at")? as u64,
+        })
+    }
+}
+
+impl Database {
+    /// Records a pending unban, replacing any existing one for the same user and guild.
+    pub fn insert_tempban(&self, ban: TempBan) -> Result<(), DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO tempbans (guild_id, user_id, moderator_id, reason, unban_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                ban.guild_id as i64,
+                ban.user_id as i64,
+                ban.moderator_id as i64,
+                ban.reason,
+                ban.unban_at as i64,
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Removes the pending unban for a user, returning it if there was one.
+    pub fn remove_tempban(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+    ) -> Result<Option<TempBan>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "DELETE FROM tempbans WHERE guild_id = ?1 AND user_id = ?2 RETURNING *",
+            params![guild_id as i64, user_id as i64],
+            TempBan::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Removes a pending unban that has been lifted, unless it was replaced in the meantime.
+    /// Returns whether it was removed.
+    pub fn remove_lifted_tempban(&self, lifted: &TempBan) -> Result<bool, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "DELETE FROM tempbans WHERE guild_id = ?1 AND user_id = ?2 AND unban_at = ?3",
+            params![
+                lifted.guild_id as i64,
+                lifted.user_id as i64,
+                lifted.unban_at as i64
+            ],
+        )
+        .map(|removed| removed > 0)
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Every pending unban whose time has come.
+    pub fn due_tempbans(&self, now: u64) -> Result<Vec<TempBan>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare("SELECT * FROM tempbans WHERE unban_at <= ?1 ORDER BY unban_at")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![now as i64], TempBan::from_row)?
+                    .collect()
+            })
+            .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ban(user_id: u64, unban_at: u64) -> TempBan {
+        TempBan {
+            guild_id: 1,
+            user_id,
+            moderator_id: 2,
+            reason: None,
+            unban_at,
+        }
+    }
+
+    #[test]
+    fn replaces_and_lifts_tempbans() {
+        let db = Database::in_memory();
+
+        db.insert_tempban(ban(10, 100)).unwrap();
+        db.insert_tempban(ban(11, 300)).unwrap();
+        db.insert_tempban(ban(10, 200)).unwrap();
+
+        assert_eq!(db.due_tempbans(250).unwrap(), vec![ban(10, 200)]);
+        assert_eq!(db.remove_tempban(1, 11).unwrap(), Some(ban(11, 300)));
+        assert_eq!(db.remove_tempban(1, 11).unwrap(), None);
+
+        // A ban re-issued after the old one came due stays pending
+        assert!(!db.remove_lifted_tempban(&ban(10, 100)).unwrap());
+        assert!(db.remove_lifted_tempban(&ban(10, 200)).unwrap());
+        assert_eq!(db.due_tempbans(250).unwrap(), vec![]);
+    }
+}
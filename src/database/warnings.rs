@@ -0,0 +1,142 @@
+use error_stack::{Report, Result};
+use rusqlite::{params, OptionalExtension, Row};
+
+use crate::duration::unix_now;
+
+use super::{Database, DatabaseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub id: i64,
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub moderator_id: u64,
+    pub reason: String,
+    /// The case the warning was recorded as, if recording it succeeded.
+    pub case_id: Option<i64>,
+    /// Unix timestamp, in seconds.
+    pub created_at: u64,
+}
+
+impl Warning {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            guild_id: row.get::<_, i64>("guild_id")? as u64,
+            user_id: row.get::<_, i64>("user_id")? as u64,
+            moderator_id: row.get::<_, i64>("moderator_id")? as u64,
+            reason: row.get("reason")?,
+            case_id: row.get("case_id")?,
+            created_at: row.get::<_, i64>("created_at")? as u64,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewWarning {
+    pub guild_id: u64,
+    pub user_id: u64,
+    pub moderator_id: u64,
+    pub reason: String,
+    pub case_id: Option<i64>,
+}
+
+impl Database {
+    pub fn add_warning(&self, warning: NewWarning) -> Result<Warning, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "INSERT INTO warnings (guild_id, user_id, moderator_id, reason, case_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             RETURNING *",
+            params![
+                warning.guild_id as i64,
+                warning.user_id as i64,
+                warning.moderator_id as i64,
+                warning.reason,
+                warning.case_id,
+                unix_now() as i64,
+            ],
+            Warning::from_row,
+        )
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// A user's warnings in a guild, newest first.
+    pub fn warnings_for(&self, guild_id: u64, user_id: u64) -> Result<Vec<Warning>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.prepare("SELECT * FROM warnings WHERE guild_id = ?1 AND user_id = ?2 ORDER BY id DESC")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![guild_id as i64, user_id as i64], Warning::from_row)?
+                    .collect()
+            })
+            .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Removes one of a user's warnings, returning it if it existed.
+    pub fn remove_warning(
+        &self,
+        guild_id: u64,
+        user_id: u64,
+        id: i64,
+    ) -> Result<Option<Warning>, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row(
+            "DELETE FROM warnings WHERE guild_id = ?1 AND user_id = ?2 AND id = ?3 RETURNING *",
+            params![guild_id as i64, user_id as i64, id],
+            Warning::from_row,
+        )
+        .optional()
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+
+    /// Removes every warning a user has in a guild, returning how many there were.
+    pub fn clear_warnings(&self, guild_id: u64, user_id: u64) -> Result<usize, DatabaseError> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "DELETE FROM warnings WHERE guild_id = ?1 AND user_id = ?2",
+            params![guild_id as i64, user_id as i64],
+        )
+        .map_err(|e| Report::from(e).change_context(DatabaseError))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_warning(user_id: u64) -> NewWarning {
+        NewWarning {
+            guild_id: 1,
+            user_id,
+            moderator_id: 2,
+            reason: String::from("spam"),
+            case_id: Some(5),
+        }
+    }
+
+    #[test]
+    fn adds_and_removes_warnings() {
+        let db = Database::in_memory();
+
+        let first = db.add_warning(new_warning(10)).unwrap();
+        let second = db.add_warning(new_warning(10)).unwrap();
+        db.add_warning(new_warning(11)).unwrap();
+
+        assert_eq!(
+            db.warnings_for(1, 10).unwrap(),
+            vec![second.clone(), first.clone()]
+        );
+        assert_eq!(db.remove_warning(1, 11, first.id).unwrap(), None);
+        assert_eq!(db.remove_warning(1, 10, first.id).unwrap(), Some(first));
+        assert_eq!(db.warnings_for(1, 10).unwrap(), vec![second]);
+
+        assert_eq!(db.clear_warnings(1, 10).unwrap(), 1);
+        assert_eq!(db.clear_warnings(1, 10).unwrap(), 0);
+        assert_eq!(db.warnings_for(1, 11).unwrap().len(), 1);
+    }
+}
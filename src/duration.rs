@@ -0,0 +1,107 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Parses a human duration such as `7d`, `12h` or `1d12h`. Supported units are `s`, `m`, `h`,
+/// `d` and `w`.
+pub fn parse(input: &str) -> Option<Duration> {
+    let mut total: u64 = 0;
+    let mut number = String::new();
+
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+
+        let value: u64 = number.parse().ok()?;
+        total = total.checked_add(value.checked_mul(unit)?)?;
+        number.clear();
+    }
+
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(total))
+}
+
+/// Formats a duration using its largest whole units, e.g. `1 day, 12 hours`.
+pub fn format(duration: Duration) -> String {
+    let units = [
+        ("week", 7 * 24 * 60 * 60),
+        ("day", 24 * 60 * 60),
+        ("hour", 60 * 60),
+        ("minute", 60),
+        ("second", 1),
+    ];
+
+    let mut remaining = duration.as_secs();
+    let mut parts = vec![];
+
+    for (name, seconds) in units {
+        let count = remaining / seconds;
+        remaining %= seconds;
+
+        if count > 0 {
+            parts.push(format!(
+                "{} {}{}",
+                count,
+                name,
+                if count == 1 { "" } else { "s" }
+            ));
+        }
+    }
+
+    if parts.is_empty() {
+        return String::from("0 seconds");
+    }
+
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_units() {
+        assert_eq!(parse("7d"), Some(Duration::from_secs(7 * 24 * 60 * 60)));
+        assert_eq!(parse("12h"), Some(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(parse("30M"), Some(Duration::from_secs(30 * 60)));
+    }
+
+    #[test]
+    fn parses_combined_units() {
+        assert_eq!(parse("1d12h"), Some(Duration::from_secs(36 * 60 * 60)));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("12"), None);
+        assert_eq!(parse("d"), None);
+        assert_eq!(parse("0h"), None);
+        assert_eq!(parse("5y"), None);
+    }
+
+    #[test]
+    fn formats_largest_units() {
+        assert_eq!(format(Duration::from_secs(36 * 60 * 60)), "1 day, 12 hours");
+        assert_eq!(format(Duration::from_secs(60)), "1 minute");
+    }
+}
@@ -1,21 +1,40 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
 use async_trait::async_trait;
 use error_stack::ResultExt;
 use serenity::{
     client::{Context, EventHandler},
-    model::{application::Interaction, gateway, id::GuildId},
+    model::{application::Interaction, gateway, guild::Member, id::GuildId},
 };
 use tracing::{error, info};
 
 use crate::{
+    api::PulsarApi,
     bot::BotStartError,
-    commands::{self, Command},
+    commands::{self, massban::PendingMassban, Command},
     config::ConfigFile,
+    database::Database,
+    jobs, onboarding, raid,
+    raid::JoinTracker,
 };
 
 pub struct BotEvents {
     pub commands: Vec<Box<dyn for<'a> Command + Send + Sync>>,
     pub http: reqwest::Client,
+    pub api: PulsarApi,
     pub cfg: ConfigFile,
+    pub db: Arc<Database>,
+    /// Mass bans waiting for their preview to be confirmed.
+    pub massbans: Mutex<HashMap<u64, PendingMassban>>,
+    /// Recent joins per guild, for raid detection.
+    pub joins: JoinTracker,
+    jobs_started: AtomicBool,
 }
 
 #[async_trait]
@@ -32,23 +51,45 @@ impl EventHandler for BotEvents {
         info!("User Id: {}", ready.user.id);
 
         for g in &self.cfg.guilds {
-            let guild = GuildId::new(g.clone() as u64);
+            let guild = GuildId::new(*g as u64);
 
             let mut commands = vec![];
 
-            for command in &self.commands {
+            for command in self.commands.iter().filter(|command| !command.global()) {
                 commands.push(command.register(self).await);
             }
 
             guild.set_commands(&ctx.http, commands).await.unwrap();
         }
+
+        let mut global_commands = vec![];
+
+        for command in self.commands.iter().filter(|command| command.global()) {
+            global_commands.push(command.register(self).await);
+        }
+
+        serenity::all::Command::set_global_commands(&ctx.http, global_commands)
+            .await
+            .unwrap();
+
+        // `ready` fires again on every reconnect, the jobs only need starting once.
+        if !self.jobs_started.swap(true, Ordering::SeqCst) {
+            jobs::start(&ctx, self);
+        }
+    }
+
+    async fn guild_member_addition(&self, ctx: Context, new_member: Member) {
+        raid::member_joined(self, &ctx, &new_member).await;
+        onboarding::member_joined(self, &ctx, &new_member).await;
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         match interaction {
             Interaction::Command(mut interaction_command) => {
                 for command in &self.commands {
-                    if &command.name() == &interaction_command.data.name.as_str() {
+                    if command.name() == interaction_command.data.name
+                        && command.kind() == interaction_command.data.kind
+                    {
                         match command.execute(self, &ctx, &mut interaction_command).await {
                             Ok(_) => {}
                             Err(err) => {
@@ -58,6 +99,50 @@ impl EventHandler for BotEvents {
                     }
                 }
             }
+            Interaction::Component(mut component) => {
+                let prefix = component
+                    .data
+                    .custom_id
+                    .split(':')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+
+                for command in &self.commands {
+                    if command.name() == prefix {
+                        if let Err(err) = command.component(self, &ctx, &mut component).await {
+                            error!("Failed to handle component: {:?}", err);
+                        }
+                    }
+                }
+            }
+            Interaction::Modal(modal) => {
+                let prefix = modal
+                    .data
+                    .custom_id
+                    .split(':')
+                    .next()
+                    .unwrap_or_default();
+
+                for command in &self.commands {
+                    if command.name() == prefix {
+                        if let Err(err) = command.modal(self, &ctx, &modal).await {
+                            error!("Failed to handle modal: {:?}", err);
+                        }
+                    }
+                }
+            }
+            Interaction::Autocomplete(autocomplete) => {
+                for command in &self.commands {
+                    if command.name() == autocomplete.data.name
+                        && command.kind() == autocomplete.data.kind
+                    {
+                        if let Err(err) = command.autocomplete(self, &ctx, &autocomplete).await {
+                            error!("Failed to handle autocomplete: {:?}", err);
+                        }
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -65,10 +150,21 @@ impl EventHandler for BotEvents {
 
 impl Default for BotEvents {
     fn default() -> Self {
+        let http = reqwest::Client::new();
+        let cfg = ConfigFile::read().change_context(BotStartError).unwrap();
+        let db = Database::open(&cfg.data_dir)
+            .change_context(BotStartError)
+            .unwrap();
+
         Self {
             commands: commands::load_commands(),
-            http: reqwest::Client::new(),
-            cfg: ConfigFile::read().change_context(BotStartError).unwrap(),
+            api: PulsarApi::new(http.clone(), &cfg.api_url, &cfg.api_key),
+            http,
+            cfg,
+            db: Arc::new(db),
+            massbans: Mutex::new(HashMap::new()),
+            joins: JoinTracker::default(),
+            jobs_started: AtomicBool::new(false),
         }
     }
 }
@@ -0,0 +1,43 @@
+use serenity::{client::Context, model::id::GuildId};
+
+use crate::event_handler::BotEvents;
+
+mod role_reconcile;
+mod unban;
+
+/// Spawns the background jobs enabled in the config. Called once, when the bot first connects.
+pub fn start(ctx: &Context, handler: &BotEvents) {
+    if let Some(settings) = &handler.cfg.role_reconcile {
+        let guilds = handler
+            .cfg
+            .guilds
+            .iter()
+            .filter_map(|guild| {
+                let guild_settings = handler.cfg.guild(*guild as u64)?;
+
+                Some((GuildId::new(*guild as u64), guild_settings.clone()))
+            })
+            .collect();
+
+        tokio::spawn(role_reconcile::run(
+            ctx.http.clone(),
+            handler.api.clone(),
+            guilds,
+            settings.clone(),
+        ));
+    }
+
+    let mod_log_channels = handler
+        .cfg
+        .guild_settings
+        .iter()
+        .filter_map(|(guild, settings)| Some((guild.parse().ok()?, settings.mod_log_channel()?)))
+        .collect();
+
+    tokio::spawn(unban::run(
+        ctx.http.clone(),
+        ctx.cache.current_user().id,
+        handler.db.clone(),
+        mod_log_channels,
+    ));
+}
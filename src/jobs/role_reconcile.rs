@@ -0,0 +1,184 @@
+use std::{sync::Arc, time::Duration};
+
+use serenity::{
+    builder::{CreateEmbed, CreateEmbedFooter, CreateMessage},
+    http::Http,
+    model::id::{ChannelId, GuildId, UserId},
+};
+use tokio::time::{self, Instant, MissedTickBehavior};
+use tracing::{error, info, warn};
+
+use crate::{
+    api::PulsarApi,
+    config::{GuildSettings, RoleReconcileSettings},
+    role_sync,
+};
+
+/// Maximum number of per-member changes listed in a report.
+const REPORTED_CHANGES: usize = 20;
+
+#[derive(Debug, Default)]
+struct Report {
+    scanned: usize,
+    linked: usize,
+    changed: usize,
+    added: usize,
+    removed: usize,
+    failed: usize,
+    changes: Vec<String>,
+}
+
+impl Report {
+    fn embed(&self, dry_run: bool, elapsed: Duration) -> CreateEmbed {
+        let title = if dry_run {
+            "Role reconciliation (dry run)"
+        } else {
+            "Role reconciliation"
+        };
+
+        let mut changes = self.changes.join("\n");
+
+        if self.changed > self.changes.len() {
+            changes.push_str(&format!(
+                "\n...and {} more",
+                self.changed - self.changes.len()
+            ));
+        }
+
+        CreateEmbed::new()
+            .title(title)
+            .field("Members checked", self.scanned.to_string(), true)
+            .field("Linked", self.linked.to_string(), true)
+            .field("Members changed", self.changed.to_string(), true)
+            .field("Roles added", self.added.to_string(), true)
+            .field("Roles removed", self.removed.to_string(), true)
+            .field("Failed", self.failed.to_string(), true)
+            .description(if changes.is_empty() {
+                String::from("No changes.")
+            } else {
+                changes
+            })
+            .footer(CreateEmbedFooter::new(format!(
+                "Took {}s",
+                elapsed.as_secs()
+            )))
+    }
+}
+
+/// Periodically reconciles the verified and addon roles of every member in every guild.
+pub async fn run(
+    http: Arc<Http>,
+    api: PulsarApi,
+    guilds: Vec<(GuildId, GuildSettings)>,
+    settings: RoleReconcileSettings,
+) {
+    let period = Duration::from_secs(settings.interval_minutes.max(1) * 60);
+    let mut interval = time::interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    info!(
+        "Role reconciliation scheduled every {} minutes{}",
+        settings.interval_minutes,
+        if settings.dry_run { " (dry run)" } else { "" }
+    );
+
+    loop {
+        interval.tick().await;
+
+        for (guild_id, guild_settings) in &guilds {
+            let started = Instant::now();
+            let report = reconcile_guild(&http, &api, *guild_id, guild_settings, &settings).await;
+
+            info!(
+                "Reconciled roles in guild {}: {} checked, {} changed, {} failed",
+                guild_id, report.scanned, report.changed, report.failed
+            );
+
+            if let Some(log_channel) = guild_settings.log_channel {
+                let message =
+                    CreateMessage::new().embed(report.embed(settings.dry_run, started.elapsed()));
+
+                if let Err(err) = ChannelId::new(log_channel)
+                    .send_message(&http, message)
+                    .await
+                {
+                    error!("Failed to post role reconciliation report: {:?}", err);
+                }
+            }
+        }
+    }
+}
+
+async fn reconcile_guild(
+    http: &Http,
+    api: &PulsarApi,
+    guild_id: GuildId,
+    guild_settings: &GuildSettings,
+    settings: &RoleReconcileSettings,
+) -> Report {
+    let mut report = Report::default();
+    let mut limiter = time::interval(Duration::from_secs(1) / settings.members_per_second.max(1));
+    limiter.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut after: Option<UserId> = None;
+
+    loop {
+        let members = match guild_id.members(http, Some(1000), after).await {
+            Ok(members) => members,
+            Err(err) => {
+                error!("Failed to list members of guild {}: {:?}", guild_id, err);
+                report.failed += 1;
+                break;
+            }
+        };
+
+        let Some(last) = members.last() else {
+            break;
+        };
+        after = Some(last.user.id);
+
+        for member in members.iter().filter(|member| !member.user.bot) {
+            limiter.tick().await;
+            report.scanned += 1;
+
+            let outcome =
+                match role_sync::sync_member(http, api, guild_settings, member, settings.dry_run)
+                    .await
+                {
+                    Ok(outcome) => outcome,
+                    Err(err) => {
+                        warn!(
+                            "Failed to reconcile roles for {}: {:?}",
+                            member.user.id, err
+                        );
+                        report.failed += 1;
+                        continue;
+                    }
+                };
+
+            if outcome.linked {
+                report.linked += 1;
+            }
+
+            if !outcome.is_unchanged() {
+                report.changed += 1;
+                report.added += outcome.added.len();
+                report.removed += outcome.removed.len();
+
+                if report.changes.len() < REPORTED_CHANGES {
+                    report.changes.push(format!(
+                        "<@{}>: {}",
+                        member.user.id,
+                        outcome.describe().replace('\n', ", ")
+                    ));
+                }
+            }
+        }
+
+        if members.len() < 1000 {
+            break;
+        }
+    }
+
+    report
+}
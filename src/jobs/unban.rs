@@ -0,0 +1,118 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use serenity::{
+    http::{Http, HttpError},
+    model::id::{ChannelId, GuildId, UserId},
+    Error,
+};
+use tokio::time::{self, MissedTickBehavior};
+use tracing::{error, info, warn};
+
+use crate::{
+    database::{
+        cases::{CaseAction, NewCase},
+        Database,
+    },
+    duration::unix_now,
+    mod_log,
+};
+
+/// How often the database is checked for bans that have run out.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// JSON error code Discord returns when the user isn't banned any more.
+const UNKNOWN_BAN: isize = 10026;
+
+/// Lifts temporary bans once they expire. Bans that ran out while the bot was offline are lifted
+/// on the first check after it starts. Each unban is recorded as a case by the bot itself, unless
+/// the ban had already been lifted by hand.
+pub async fn run(
+    http: Arc<Http>,
+    bot_id: UserId,
+    db: Arc<Database>,
+    mod_log_channels: HashMap<u64, ChannelId>,
+) {
+    let mut interval = time::interval(CHECK_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let due = match db.due_tempbans(unix_now()) {
+            Ok(due) => due,
+            Err(err) => {
+                error!("Failed to load expired temporary bans: {:?}", err);
+                continue;
+            }
+        };
+
+        for ban in due {
+            let reason = format!(
+                "Temporary ban expired{}",
+                ban.reason
+                    .as_deref()
+                    .map(|reason| format!(": {}", reason))
+                    .unwrap_or_default()
+            );
+
+            let lifted = match http
+                .remove_ban(
+                    GuildId::new(ban.guild_id),
+                    UserId::new(ban.user_id),
+                    Some(&reason),
+                )
+                .await
+            {
+                Ok(()) => {
+                    info!(
+                        "Lifted temporary ban of {} in {}",
+                        ban.user_id, ban.guild_id
+                    );
+                    true
+                }
+                Err(Error::Http(HttpError::UnsuccessfulRequest(response)))
+                    if response.error.code == UNKNOWN_BAN =>
+                {
+                    info!(
+                        "Temporary ban of {} in {} was already lifted",
+                        ban.user_id, ban.guild_id
+                    );
+                    false
+                }
+                Err(err) => {
+                    // Left in the database so the next check retries it.
+                    warn!(
+                        "Failed to lift temporary ban of {} in {}: {:?}",
+                        ban.user_id, ban.guild_id, err
+                    );
+                    continue;
+                }
+            };
+
+            // A ban re-issued since this one came due replaces it, and has to stay
+            if let Err(err) = db.remove_lifted_tempban(&ban) {
+                error!("Failed to remove expired temporary ban: {:?}", err);
+            }
+
+            // Whoever lifted it by hand already has a case for it
+            if !lifted {
+                continue;
+            }
+
+            mod_log::record(
+                &http,
+                &db,
+                mod_log_channels.get(&ban.guild_id).copied(),
+                NewCase {
+                    guild_id: ban.guild_id,
+                    action: CaseAction::Unban,
+                    target_id: ban.user_id,
+                    moderator_id: bot_id.get(),
+                    reason: Some(reason),
+                    duration: None,
+                },
+            )
+            .await;
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use error_stack::{Context, Report, Result};
+use serenity::{
+    http::Http,
+    model::{
+        channel::{PermissionOverwrite, PermissionOverwriteType},
+        id::{ChannelId, RoleId},
+        Permissions,
+    },
+};
+
+use crate::database::{lockdowns::Lockdown, Database};
+
+/// Permissions a locked channel denies.
+const LOCKED_PERMISSIONS: Permissions = Permissions::SEND_MESSAGES
+    .union(Permissions::SEND_MESSAGES_IN_THREADS)
+    .union(Permissions::CREATE_PUBLIC_THREADS)
+    .union(Permissions::CREATE_PRIVATE_THREADS);
+
+#[derive(Debug)]
+pub struct LockdownError;
+
+impl fmt::Display for LockdownError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Error locking or unlocking a channel")
+    }
+}
+
+impl Context for LockdownError {}
+
+/// Sets the role's overwrite in the channel, deleting it instead when it would be empty, as an
+/// empty overwrite only clutters the channel's settings.
+async fn set_overwrite(
+    http: &Http,
+    channel_id: ChannelId,
+    kind: PermissionOverwriteType,
+    allow: Permissions,
+    deny: Permissions,
+) -> serenity::Result<()> {
+    if allow.is_empty() && deny.is_empty() {
+        channel_id.delete_permission(http, kind).await
+    } else {
+        channel_id
+            .create_permission(http, PermissionOverwrite { allow, deny, kind })
+            .await
+    }
+}
+
+/// Locks or unlocks a channel for a role. Locking denies the role the permission to send
+/// messages and records its overwrite from before, which unlocking restores exactly. Returns
+/// `false` when the channel was already locked, or wasn't locked by the bot when unlocking, so
+/// channels that are read-only by design stay that way.
+pub async fn set_locked(
+    http: &Http,
+    db: &Database,
+    channel_id: ChannelId,
+    role: RoleId,
+    locked: bool,
+) -> Result<bool, LockdownError> {
+    let kind = PermissionOverwriteType::Role(role);
+
+    if !locked {
+        let Some(lockdown) = db
+            .lockdown(channel_id.get(), role.get())
+            .map_err(|e| e.change_context(LockdownError))?
+        else {
+            return Ok(false);
+        };
+
+        let (allow, deny) = lockdown.previous.unwrap_or_default();
+
+        set_overwrite(
+            http,
+            channel_id,
+            kind,
+            Permissions::from_bits_retain(allow),
+            Permissions::from_bits_retain(deny),
+        )
+        .await
+        .map_err(|e| Report::from(e).change_context(LockdownError))?;
+
+        db.remove_lockdown(channel_id.get(), role.get())
+            .map_err(|e| e.change_context(LockdownError))?;
+
+        return Ok(true);
+    }
+
+    let channel = channel_id
+        .to_channel(http)
+        .await
+        .map_err(|e| Report::from(e).change_context(LockdownError))?;
+    let previous = channel
+        .guild()
+        .and_then(|channel| {
+            channel
+                .permission_overwrites
+                .into_iter()
+                .find(|overwrite| overwrite.kind == kind)
+        })
+        .map(|overwrite| (overwrite.allow, overwrite.deny));
+
+    let (allow, deny) = previous.unwrap_or_default();
+
+    if deny.contains(Permissions::SEND_MESSAGES) {
+        return Ok(false);
+    }
+
+    // Recorded first, so a channel is never locked without a way back
+    db.record_lockdown(&Lockdown {
+        channel_id: channel_id.get(),
+        role_id: role.get(),
+        previous: previous.map(|(allow, deny)| (allow.bits(), deny.bits())),
+    })
+    .map_err(|e| e.change_context(LockdownError))?;
+
+    if let Err(err) = set_overwrite(
+        http,
+        channel_id,
+        kind,
+        allow - LOCKED_PERMISSIONS,
+        deny | LOCKED_PERMISSIONS,
+    )
+    .await
+    {
+        let report = Report::from(err).change_context(LockdownError);
+
+        return Err(match db.remove_lockdown(channel_id.get(), role.get()) {
+            Ok(_) => report,
+            Err(remove_err) => report.attach_printable(format!(
+                "Failed to forget the lockdown afterwards: {:?}",
+                remove_err
+            )),
+        });
+    }
+
+    Ok(true)
+}
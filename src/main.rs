@@ -7,10 +7,20 @@ use tracing_subscriber::FmtSubscriber;
 
 use bot::start;
 
+mod api;
 mod bot;
 mod commands;
 mod config;
+mod database;
+mod duration;
 mod event_handler;
+mod jobs;
+mod lockdown;
+mod mod_log;
+mod models;
+mod onboarding;
+mod raid;
+mod role_sync;
 
 #[derive(Debug)]
 struct ApplicationInitialisationError;
@@ -0,0 +1,73 @@
+use serenity::{
+    builder::{CreateEmbed, CreateMessage},
+    http::Http,
+    model::{id::ChannelId, Timestamp},
+};
+use tracing::error;
+
+use crate::{
+    database::{
+        cases::{Case, NewCase},
+        Database,
+    },
+    duration,
+};
+
+pub fn case_embed(case: &Case) -> CreateEmbed {
+    let mut embed = CreateEmbed::new()
+        .title(format!("Case #{} - {}", case.id, case.action))
+        .field(
+            "User",
+            format!("<@{}> ({})", case.target_id, case.target_id),
+            true,
+        )
+        .field("Moderator", format!("<@{}>", case.moderator_id), true)
+        .field(
+            "Reason",
+            case.reason.as_deref().unwrap_or("No reason given"),
+            false,
+        );
+
+    if let Some(duration) = case.duration {
+        embed = embed.field("Duration", duration::format(duration), true);
+    }
+
+    if let Ok(timestamp) = Timestamp::from_unix_timestamp(case.created_at as i64) {
+        embed = embed.timestamp(timestamp);
+    }
+
+    embed
+}
+
+/// Opens a case and posts it to the guild's mod log. The action it records has already happened,
+/// so failures are logged rather than returned.
+pub async fn record(
+    http: &Http,
+    db: &Database,
+    mod_log_channel: Option<ChannelId>,
+    case: NewCase,
+) -> Option<Case> {
+    let case = match db.create_case(case) {
+        Ok(case) => case,
+        Err(err) => {
+            error!("Failed to record moderation case: {:?}", err);
+            return None;
+        }
+    };
+
+    post(http, mod_log_channel, &case).await;
+
+    Some(case)
+}
+
+/// Posts a case that's already been opened to the guild's mod log, logging any failure.
+pub async fn post(http: &Http, mod_log_channel: Option<ChannelId>, case: &Case) {
+    if let Some(channel) = mod_log_channel {
+        if let Err(err) = channel
+            .send_message(http, CreateMessage::new().embed(case_embed(case)))
+            .await
+        {
+            error!("Failed to post case #{} to the mod log: {:?}", case.id, err);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Data exchanged with the Pulsar Link backend.
+//!
+//! The backend speaks camelCase. Older deployments sent snake_case, so every renamed field also
+//! accepts its snake_case alias until all backends have been migrated.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct User {
+    pub id: Option<String>,
+    #[serde(rename = "steamId", alias = "steam_id")]
+    pub steam_id: Option<u64>,
+    #[serde(rename = "gmodstoreId", alias = "gmodstore_id")]
+    pub gmodstore_id: Option<String>,
+    #[serde(rename = "discordId", alias = "discord_id")]
+    pub discord_id: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Addon {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ErrorResponse {
+    pub error: Option<String>,
+    /// Pulsar ID of the user that already owns one of the identities (409 only).
+    pub id: Option<String>,
+    /// Per-field validation messages (400/422 only).
+    #[serde(default)]
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Query parameters for `POST /user`.
+#[derive(Debug, Serialize)]
+pub struct CreateUser {
+    #[serde(rename = "steamId")]
+    pub steam_id: u64,
+    #[serde(rename = "gmodstoreId")]
+    pub gmodstore_id: String,
+    #[serde(rename = "discordId")]
+    pub discord_id: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_contract() {
+        let user: User = serde_json::from_str(include_str!("fixtures/user.json")).unwrap();
+
+        assert_eq!(
+            user.id.as_deref(),
+            Some("8f0c6a4e-3b9d-4f3e-9a51-0c7d2b1e6f42")
+        );
+        assert_eq!(user.steam_id, Some(76561198012345678));
+        assert_eq!(
+            user.gmodstore_id.as_deref(),
+            Some("9b7f2d4e-1c3a-4e5f-8a6b-7c8d9e0f1a2b")
+        );
+        assert_eq!(user.discord_id, Some(189412378653442048));
+        assert_eq!(user.error, None);
+    }
+
+    #[test]
+    fn user_accepts_snake_case() {
+        let camel: User = serde_json::from_str(include_str!("fixtures/user.json")).unwrap();
+        let snake: User =
+            serde_json::from_str(include_str!("fixtures/user_snake_case.json")).unwrap();
+
+        assert_eq!(snake.steam_id, camel.steam_id);
+        assert_eq!(snake.gmodstore_id, camel.gmodstore_id);
+        assert_eq!(snake.discord_id, camel.discord_id);
+    }
+
+    #[test]
+    fn user_not_found_contract() {
+        let user: User =
+            serde_json::from_str(include_str!("fixtures/user_not_found.json")).unwrap();
+
+        assert_eq!(user.id, None);
+        assert_eq!(user.error.as_deref(), Some("User not found"));
+    }
+
+    #[test]
+    fn addons_contract() {
+        let addons: Vec<Addon> =
+            serde_json::from_str(include_str!("fixtures/addons.json")).unwrap();
+
+        assert_eq!(addons.len(), 2);
+        assert_eq!(addons[0].name.as_deref(), Some("Pulsar Lib"));
+        assert_eq!(
+            addons[1].id.as_deref(),
+            Some("6a7b8c9d-0e1f-4a2b-3c4d-5e6f7a8b9c0d")
+        );
+    }
+
+    #[test]
+    fn error_contract() {
+        let conflict: ErrorResponse =
+            serde_json::from_str(include_str!("fixtures/error_conflict.json")).unwrap();
+
+        assert_eq!(conflict.error.as_deref(), Some("User already exists"));
+        assert_eq!(
+            conflict.id.as_deref(),
+            Some("8f0c6a4e-3b9d-4f3e-9a51-0c7d2b1e6f42")
+        );
+        assert!(conflict.errors.is_empty());
+
+        let validation: ErrorResponse =
+            serde_json::from_str(include_str!("fixtures/error_validation.json")).unwrap();
+
+        assert_eq!(validation.errors.len(), 2);
+        assert_eq!(
+            validation.errors.get("steamId").map(String::as_str),
+            Some("Must be a valid SteamID64")
+        );
+    }
+
+    #[test]
+    fn create_user_contract() {
+        let params = CreateUser {
+            steam_id: 76561198012345678,
+            gmodstore_id: String::from("9b7f2d4e-1c3a-4e5f-8a6b-7c8d9e0f1a2b"),
+            discord_id: 189412378653442048,
+        };
+
+        assert_eq!(
+            serde_urlencoded::to_string(&params).unwrap(),
+            "steamId=76561198012345678&gmodstoreId=9b7f2d4e-1c3a-4e5f-8a6b-7c8d9e0f1a2b&discordId=189412378653442048"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use serenity::{
+    builder::{CreateAllowedMentions, CreateMessage},
+    client::Context,
+    model::{guild::Member, id::ChannelId},
+};
+use tracing::{error, info, warn};
+
+use crate::{commands::verify, event_handler::BotEvents, role_sync};
+
+/// Gives linked members their roles straight away and points everyone else at verification.
+pub async fn member_joined(handler: &BotEvents, ctx: &Context, member: &Member) {
+    if member.user.bot {
+        return;
+    }
+
+    let Some(settings) = handler.cfg.guild(member.guild_id.get()) else {
+        return;
+    };
+
+    match role_sync::sync_member(&ctx.http, &handler.api, settings, member, false).await {
+        Ok(outcome) if outcome.linked => {
+            info!(
+                "{} joined {} and is already linked. {}",
+                member.user.id,
+                member.guild_id,
+                outcome.describe().replace('\n', ", ")
+            );
+
+            return;
+        }
+        Ok(_) => {}
+        Err(err) => {
+            // Don't onboard someone who may well be linked already.
+            error!(
+                "Failed to sync roles for new member {}: {:?}",
+                member.user.id, err
+            );
+
+            return;
+        }
+    }
+
+    let Some(onboarding) = &settings.onboarding else {
+        return;
+    };
+
+    let (embed, components) = verify::verification_message(
+        Some(&settings.verify),
+        onboarding.message.as_deref(),
+        member.user.id,
+    );
+
+    let message = CreateMessage::new().embed(embed).components(components);
+
+    if member
+        .user
+        .direct_message(ctx, message.clone())
+        .await
+        .is_ok()
+    {
+        return;
+    }
+
+    let Some(fallback_channel) = onboarding.fallback_channel else {
+        warn!("Unable to DM onboarding message to {}", member.user.id);
+
+        return;
+    };
+
+    let message = message
+        .content(format!("<@{}>", member.user.id))
+        .allowed_mentions(CreateAllowedMentions::new().users([member.user.id]));
+
+    if let Err(err) = ChannelId::new(fallback_channel)
+        .send_message(&ctx.http, message)
+        .await
+    {
+        error!(
+            "Failed to post onboarding message for {}: {:?}",
+            member.user.id, err
+        );
+    }
+}
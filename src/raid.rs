@@ -0,0 +1,392 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    builder::{CreateEmbed, CreateMessage},
+    client::Context,
+    model::{
+        guild::Member,
+        id::{ChannelId, GuildId, UserId},
+    },
+};
+use tracing::{error, warn};
+
+use crate::{
+    commands::{
+        massban::{self, PendingMassban},
+        timeout::{apply_timeout, MAX_TIMEOUT},
+    },
+    config::{GuildSettings, RaidDetectionSettings},
+    duration::{self, unix_now},
+    event_handler::BotEvents,
+    lockdown,
+};
+
+/// Flagged accounts mentioned individually in an alert.
+const LISTED_ACCOUNTS: usize = 30;
+
+/// Keys the mass bans offered by alerts. `/massban` previews are keyed by interaction ID, a
+/// snowflake far larger than this counter will ever get.
+static ALERT_KEYS: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Copy)]
+struct Join {
+    at: Instant,
+    user: UserId,
+    new_account: bool,
+}
+
+/// A burst of joins that crossed a guild's thresholds.
+#[derive(Debug, PartialEq, Eq)]
+struct Raid {
+    joins: usize,
+    new_accounts: usize,
+    flagged: Vec<UserId>,
+}
+
+#[derive(Debug, Default)]
+struct JoinWindow {
+    joins: VecDeque<Join>,
+    /// Joins aren't checked again until this passes, so one raid raises one alert per window.
+    quiet_until: Option<Instant>,
+}
+
+impl JoinWindow {
+    /// Records a join, returning the raid it completes if the window crosses the thresholds.
+    fn record(&mut self, join: Join, settings: &RaidDetectionSettings) -> Option<Raid> {
+        let window = Duration::from_secs(settings.window_seconds);
+
+        while self
+            .joins
+            .front()
+            .is_some_and(|oldest| join.at.duration_since(oldest.at) > window)
+        {
+            self.joins.pop_front();
+        }
+
+        self.joins.push_back(join);
+
+        if self.quiet_until.is_some_and(|until| join.at < until) {
+            return None;
+        }
+
+        let joins = self.joins.len();
+        let new_accounts = self.joins.iter().filter(|join| join.new_account).count();
+
+        if joins < settings.joins
+            || (new_accounts as f64) < settings.new_account_ratio * joins as f64
+        {
+            return None;
+        }
+
+        let flagged = self
+            .joins
+            .drain(..)
+            .filter(|join| settings.new_account_ratio <= 0.0 || join.new_account)
+            .map(|join| join.user)
+            .collect();
+
+        self.quiet_until = Some(join.at + window);
+
+        Some(Raid {
+            joins,
+            new_accounts,
+            flagged,
+        })
+    }
+}
+
+/// Recent joins in every guild with raid detection enabled.
+#[derive(Debug, Default)]
+pub struct JoinTracker {
+    guilds: Mutex<HashMap<GuildId, JoinWindow>>,
+}
+
+impl JoinTracker {
+    fn record(
+        &self,
+        guild_id: GuildId,
+        join: Join,
+        settings: &RaidDetectionSettings,
+    ) -> Option<Raid> {
+        self.guilds
+            .lock()
+            .unwrap()
+            .entry(guild_id)
+            .or_default()
+            .record(join, settings)
+    }
+}
+
+/// Tracks a new member, alerting moderators and taking the configured actions if they complete a
+/// raid.
+pub async fn member_joined(handler: &BotEvents, ctx: &Context, member: &Member) {
+    if member.user.bot {
+        return;
+    }
+
+    let Some(guild_settings) = handler.cfg.guild(member.guild_id.get()) else {
+        return;
+    };
+
+    let Some(settings) = &guild_settings.raid_detection else {
+        return;
+    };
+
+    let Some(new_account_age) = duration::parse(&settings.new_account_age) else {
+        warn!(
+            "Invalid raid-detection new-account-age `{}` in guild {}",
+            settings.new_account_age, member.guild_id
+        );
+        return;
+    };
+
+    let account_age = unix_now() as i64 - member.user.id.created_at().unix_timestamp();
+    let join = Join {
+        at: Instant::now(),
+        user: member.user.id,
+        new_account: account_age < new_account_age.as_secs() as i64,
+    };
+
+    let Some(raid) = handler.joins.record(member.guild_id, join, settings) else {
+        return;
+    };
+
+    warn!(
+        "Possible raid in guild {}: {} joins, {} new accounts",
+        member.guild_id, raid.joins, raid.new_accounts
+    );
+
+    respond(
+        handler,
+        ctx,
+        member.guild_id,
+        guild_settings,
+        settings,
+        raid,
+    )
+    .await;
+}
+
+/// Takes the configured automatic actions and posts an alert offering to mass ban the flagged
+/// accounts.
+async fn respond(
+    handler: &BotEvents,
+    ctx: &Context,
+    guild_id: GuildId,
+    guild_settings: &GuildSettings,
+    settings: &RaidDetectionSettings,
+    raid: Raid,
+) {
+    let mut actions = vec![];
+
+    if settings.auto_lockdown {
+        let role = guild_settings.lockdown.role(guild_id);
+        let channels = &guild_settings.lockdown.channels;
+        let mut locked = 0;
+
+        for channel in channels {
+            match lockdown::set_locked(&ctx.http, &handler.db, ChannelId::new(*channel), role, true)
+                .await
+            {
+                Ok(_) => locked += 1,
+                Err(err) => error!("Failed to lock channel {}: {:?}", channel, err),
+            }
+        }
+
+        actions.push(if channels.is_empty() {
+            String::from("No lockdown channels are configured to lock")
+        } else {
+            format!("Locked {} of {} channels", locked, channels.len())
+        });
+    }
+
+    if let Some(input) = &settings.auto_timeout {
+        match duration::parse(input) {
+            Some(length) if !raid.flagged.is_empty() => {
+                let length = length.min(MAX_TIMEOUT);
+                let bot_id = ctx.cache.current_user().id;
+                let mut timed_out = 0;
+
+                for user in &raid.flagged {
+                    match apply_timeout(
+                        handler,
+                        ctx,
+                        guild_id,
+                        bot_id,
+                        *user,
+                        length,
+                        Some("Raid detection"),
+                    )
+                    .await
+                    {
+                        Ok(_) => timed_out += 1,
+                        Err(err) => error!("Failed to time out raid account {}: {:?}", user, err),
+                    }
+                }
+
+                actions.push(format!(
+                    "Timed out {} of {} flagged accounts for {}",
+                    timed_out,
+                    raid.flagged.len(),
+                    duration::format(length)
+                ));
+            }
+            Some(_) => {}
+            None => actions.push(format!(
+                "`auto-timeout = \"{}\"` isn't a valid duration",
+                input
+            )),
+        }
+    }
+
+    let Some(channel) = settings
+        .alert_channel
+        .map(ChannelId::new)
+        .or_else(|| guild_settings.mod_log_channel())
+    else {
+        warn!(
+            "Raid detected in guild {} but there's no channel to alert",
+            guild_id
+        );
+        return;
+    };
+
+    let mut description = raid
+        .flagged
+        .iter()
+        .take(LISTED_ACCOUNTS)
+        .map(|user| format!("<@{}>", user))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if raid.flagged.len() > LISTED_ACCOUNTS {
+        description.push_str(&format!(
+            " ...and {} more",
+            raid.flagged.len() - LISTED_ACCOUNTS
+        ));
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Possible raid")
+        .description(if description.is_empty() {
+            String::from("No accounts flagged.")
+        } else {
+            description
+        })
+        .field(
+            "Joins",
+            format!(
+                "{} in {}",
+                raid.joins,
+                duration::format(Duration::from_secs(settings.window_seconds))
+            ),
+            true,
+        )
+        .field("New accounts", raid.new_accounts.to_string(), true)
+        .field(
+            "Automatic actions",
+            if actions.is_empty() {
+                String::from("None")
+            } else {
+                actions.join("\n")
+            },
+            false,
+        );
+
+    let mut message = CreateMessage::new().embed(embed);
+
+    if !raid.flagged.is_empty() {
+        let key = ALERT_KEYS.fetch_add(1, Ordering::Relaxed);
+        message = message.components(massban::buttons(key, raid.flagged.len(), "Dismiss"));
+
+        massban::store(
+            handler,
+            key,
+            PendingMassban::new(
+                guild_id,
+                None,
+                raid.flagged,
+                Some(String::from("Raid detection")),
+                None,
+            ),
+        );
+    }
+
+    if let Err(err) = channel.send_message(&ctx.http, message).await {
+        error!("Failed to post raid alert in guild {}: {:?}", guild_id, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(new_account_ratio: f64) -> RaidDetectionSettings {
+        RaidDetectionSettings {
+            joins: 3,
+            window_seconds: 60,
+            new_account_age: String::from("7d"),
+            new_account_ratio,
+            alert_channel: None,
+            auto_timeout: None,
+            auto_lockdown: false,
+        }
+    }
+
+    fn join(start: Instant, secs: u64, user: u64, new_account: bool) -> Join {
+        Join {
+            at: start + Duration::from_secs(secs),
+            user: UserId::new(user),
+            new_account,
+        }
+    }
+
+    #[test]
+    fn detects_join_bursts() {
+        let settings = settings(0.0);
+        let start = Instant::now();
+        let mut window = JoinWindow::default();
+
+        assert_eq!(window.record(join(start, 0, 1, false), &settings), None);
+        // The first join has left the window by the time the third arrives
+        assert_eq!(window.record(join(start, 30, 2, false), &settings), None);
+        assert_eq!(window.record(join(start, 80, 3, true), &settings), None);
+
+        let raid = window.record(join(start, 85, 4, false), &settings).unwrap();
+        assert_eq!(raid.joins, 3);
+        assert_eq!(raid.new_accounts, 1);
+        assert_eq!(raid.flagged.len(), 3);
+
+        // Quiet for a window after alerting
+        for user in 5..10 {
+            assert_eq!(
+                window.record(join(start, 110, user, false), &settings),
+                None
+            );
+        }
+        assert!(window
+            .record(join(start, 161, 10, false), &settings)
+            .is_some());
+    }
+
+    #[test]
+    fn flags_new_accounts_when_a_ratio_is_set() {
+        let settings = settings(0.5);
+        let start = Instant::now();
+        let mut window = JoinWindow::default();
+
+        window.record(join(start, 0, 1, false), &settings);
+        window.record(join(start, 1, 2, false), &settings);
+        assert_eq!(window.record(join(start, 2, 3, true), &settings), None);
+
+        let raid = window.record(join(start, 3, 4, true), &settings).unwrap();
+        assert_eq!(raid.flagged, vec![UserId::new(3), UserId::new(4)]);
+    }
+}
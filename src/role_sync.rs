@@ -0,0 +1,223 @@
+use std::{collections::HashSet, fmt};
+
+use error_stack::{Context, Report, Result, ResultExt};
+use serenity::{
+    http::Http,
+    model::{guild::Member, id::RoleId},
+};
+
+use crate::{api::PulsarApi, config::GuildSettings};
+
+static AUDIT_LOG_REASON: &str = "Pulsar Link role sync";
+
+#[derive(Debug)]
+pub struct RoleSyncError;
+
+impl fmt::Display for RoleSyncError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Failed to sync member roles")
+    }
+}
+
+impl Context for RoleSyncError {}
+
+#[derive(Debug, Default)]
+pub struct RoleSyncOutcome {
+    pub linked: bool,
+    pub added: Vec<RoleId>,
+    pub removed: Vec<RoleId>,
+}
+
+impl RoleSyncOutcome {
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// A short human readable summary, mentioning the changed roles.
+    pub fn describe(&self) -> String {
+        if self.is_unchanged() {
+            return String::from("Roles are already up to date.");
+        }
+
+        let mention = |roles: &[RoleId]| {
+            roles
+                .iter()
+                .map(|role| format!("<@&{}>", role))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut lines = vec![];
+
+        if !self.added.is_empty() {
+            lines.push(format!("Added: {}", mention(&self.added)));
+        }
+
+        if !self.removed.is_empty() {
+            lines.push(format!("Removed: {}", mention(&self.removed)));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Every role the sync is allowed to add or remove in a guild.
+fn managed_roles(settings: &GuildSettings) -> HashSet<RoleId> {
+    settings
+        .verified_role
+        .iter()
+        .chain(settings.addon_roles.values())
+        .map(|role| RoleId::new(*role))
+        .collect()
+}
+
+/// Works out which managed roles a member is missing and which they should no longer have.
+fn plan(
+    current: &[RoleId],
+    managed: &HashSet<RoleId>,
+    desired: &HashSet<RoleId>,
+) -> (Vec<RoleId>, Vec<RoleId>) {
+    let mut add: Vec<RoleId> = desired
+        .iter()
+        .filter(|role| !current.contains(role))
+        .copied()
+        .collect();
+    let mut remove: Vec<RoleId> = current
+        .iter()
+        .filter(|role| managed.contains(role) && !desired.contains(role))
+        .copied()
+        .collect();
+
+    add.sort();
+    remove.sort();
+
+    (add, remove)
+}
+
+/// Resolves the roles a Discord user should hold from their Pulsar Link account and addons.
+async fn desired_roles(
+    api: &PulsarApi,
+    settings: &GuildSettings,
+    member: &Member,
+) -> Result<(bool, HashSet<RoleId>), RoleSyncError> {
+    let user = api
+        .user_by_discord(member.user.id)
+        .await
+        .change_context(RoleSyncError)?;
+
+    let Some(pulsar_id) = user.and_then(|user| user.id) else {
+        return Ok((false, HashSet::new()));
+    };
+
+    let mut desired: HashSet<RoleId> = settings
+        .verified_role
+        .map(RoleId::new)
+        .into_iter()
+        .collect();
+
+    if !settings.addon_roles.is_empty() {
+        let addons = api
+            .user_addons(&pulsar_id)
+            .await
+            .change_context(RoleSyncError)?;
+
+        desired.extend(
+            addons
+                .iter()
+                .filter(|addon| addon.error.is_none())
+                .filter_map(|addon| settings.addon_roles.get(addon.id.as_deref()?))
+                .map(|role| RoleId::new(*role)),
+        );
+    }
+
+    Ok((true, desired))
+}
+
+/// Grants and revokes a member's verified and addon roles so they match Pulsar Link. With
+/// `dry_run` set the outcome describes the changes without applying them.
+pub async fn sync_member(
+    http: &Http,
+    api: &PulsarApi,
+    settings: &GuildSettings,
+    member: &Member,
+    dry_run: bool,
+) -> Result<RoleSyncOutcome, RoleSyncError> {
+    let (linked, desired) = desired_roles(api, settings, member).await?;
+    let (added, removed) = plan(&member.roles, &managed_roles(settings), &desired);
+
+    if dry_run {
+        return Ok(RoleSyncOutcome {
+            linked,
+            added,
+            removed,
+        });
+    }
+
+    for role in &added {
+        http.add_member_role(
+            member.guild_id,
+            member.user.id,
+            *role,
+            Some(AUDIT_LOG_REASON),
+        )
+        .await
+        .map_err(|e| {
+            Report::from(e)
+                .change_context(RoleSyncError)
+                .attach_printable(format!("Failed to add role {}", role))
+        })?;
+    }
+
+    for role in &removed {
+        http.remove_member_role(
+            member.guild_id,
+            member.user.id,
+            *role,
+            Some(AUDIT_LOG_REASON),
+        )
+        .await
+        .map_err(|e| {
+            Report::from(e)
+                .change_context(RoleSyncError)
+                .attach_printable(format!("Failed to remove role {}", role))
+        })?;
+    }
+
+    Ok(RoleSyncOutcome {
+        linked,
+        added,
+        removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roles(ids: &[u64]) -> Vec<RoleId> {
+        ids.iter().map(|id| RoleId::new(*id)).collect()
+    }
+
+    #[test]
+    fn plan_only_touches_managed_roles() {
+        let current = roles(&[1, 2, 10]);
+        let managed = roles(&[1, 2, 3]).into_iter().collect();
+        let desired = roles(&[1, 3]).into_iter().collect();
+
+        let (add, remove) = plan(&current, &managed, &desired);
+
+        assert_eq!(add, roles(&[3]));
+        assert_eq!(remove, roles(&[2]));
+    }
+
+    #[test]
+    fn plan_removes_everything_when_unlinked() {
+        let current = roles(&[1, 2, 10]);
+        let managed = roles(&[1, 2, 3]).into_iter().collect();
+
+        let (add, remove) = plan(&current, &managed, &HashSet::new());
+
+        assert!(add.is_empty());
+        assert_eq!(remove, roles(&[1, 2]));
+    }
+}